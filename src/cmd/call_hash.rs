@@ -1,32 +1,175 @@
+use crate::cmd::script::script_transaction;
+use crate::cmd::script_args::ScriptFunctionArguments;
 use crate::cmd::{read_bytes, script_args::args::HexEncodedBytes};
 
-use anyhow::Result;
-use blake2::{Blake2s256, Digest};
-use clap::Parser;
+use anyhow::{Error, Result};
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Blake2s256, Digest};
+use clap::{ArgGroup, Parser};
+use sha3::Keccak256;
+use std::hash::Hasher;
 use std::path::PathBuf;
+use std::str::FromStr;
+use twox_hash::XxHash64;
 
 /// Calculates the script transaction hash value.
 #[derive(Parser, Debug)]
 #[clap(about = "Generate call hash for script transaction")]
+#[clap(group(
+    ArgGroup::new("transaction")
+        .required(true)
+        .args(&["script-transaction-path", "compiled-script-path"]),
+))]
 pub struct CallHash {
     /// Path to script transaction file (*.mvt) for a script execution.
     #[clap(short, long)]
-    script_transaction_path: PathBuf,
+    script_transaction_path: Option<PathBuf>,
+
+    /// Path to the compiled script, the transaction is created from it and the given arguments.
+    #[clap(
+        short,
+        long,
+        help = "Path for the compiled Move script (used with --args and --type-args)"
+    )]
+    compiled_script_path: Option<PathBuf>,
+
+    /// Arguments for script functions.
+    #[clap(flatten)]
+    script_function_args: ScriptFunctionArguments,
+
+    /// Hash algorithm used by the target runtime.
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "blake2s-256",
+        help = "Hash algorithm used by the target runtime"
+    )]
+    algorithm: HashAlgorithm,
+
+    #[clap(
+        long,
+        help = "Verify the given hex-encoded hash against the transaction"
+    )]
+    verify: Option<String>,
+}
+
+/// Supported call hash algorithms.
+#[derive(clap::ArgEnum, Clone, Copy, Debug)]
+pub(crate) enum HashAlgorithm {
+    /// Blake2s with the 256-bit output, used by pallet-move.
+    #[clap(name = "blake2s-256")]
+    Blake2s256,
+    /// Blake2b with the 256-bit output.
+    #[clap(name = "blake2b-256")]
+    Blake2b256,
+    /// Keccak with the 256-bit output.
+    Keccak,
+    /// XX hash with the 256-bit output, as used by Substrate.
+    Twox,
+}
+
+impl HashAlgorithm {
+    /// Calculates the hash of the data.
+    pub(crate) fn hash(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Blake2s256 => Blake2s256::digest(data).into(),
+            Self::Blake2b256 => Blake2b::<U32>::digest(data).into(),
+            Self::Keccak => Keccak256::digest(data).into(),
+            Self::Twox => twox_256(data),
+        }
+    }
 }
 
 impl CallHash {
     /// Executes the command.
     pub fn execute(&self) -> Result<()> {
-        let script_tx = read_bytes(&self.script_transaction_path)?;
-
-        let mut hasher = Blake2s256::new();
-        hasher.update(&script_tx[..]);
-        let call_hash: [u8; 32] = hasher.finalize().into();
+        let script_tx = match (&self.script_transaction_path, &self.compiled_script_path) {
+            (Some(path), _) => read_bytes(path)?,
+            (None, Some(path)) => script_transaction(path, &self.script_function_args)?.encode()?,
+            // Use unreachable: the argument group requires one of the paths.
+            (None, None) => unreachable!(),
+        };
 
+        let call_hash = self.algorithm.hash(&script_tx);
         let call_hash_hex = HexEncodedBytes::from(call_hash);
 
         println!("Call hash: {call_hash_hex}");
 
+        if let Some(ref expected) = self.verify {
+            if HexEncodedBytes::from_str(expected)? != call_hash_hex {
+                return Err(Error::msg(format!(
+                    "Call hash mismatch: expected {expected}, calculated {call_hash_hex}"
+                )));
+            }
+            println!("Call hash verified");
+        }
+
         Ok(())
     }
 }
+
+/// Calculates the call hash used by pallet-move for tracking the multisig script execution.
+pub(crate) fn call_hash(script_tx: &[u8]) -> [u8; 32] {
+    HashAlgorithm::Blake2s256.hash(script_tx)
+}
+
+/// Calculates the 256-bit XX hash as four 64-bit hashes with the seeds 0 to 3, like Substrate's
+/// `twox_256`.
+fn twox_256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    for (seed, chunk) in hash.chunks_exact_mut(8).enumerate() {
+        let mut hasher = XxHash64::with_seed(seed as u64);
+        hasher.write(data);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_hex(algorithm: HashAlgorithm, data: &[u8]) -> String {
+        hex::encode(algorithm.hash(data))
+    }
+
+    #[test]
+    fn twox_256_starts_with_twox_128() {
+        // Substrate's `twox_128(b"System")`, the storage prefix of the System pallet.
+        let hash = twox_256(b"System");
+
+        assert_eq!(hex::encode(&hash[..16]), "26aa394eea5630e07c48ae0c9558cef7");
+    }
+
+    #[test]
+    fn hash_algorithms() {
+        assert_eq!(
+            hash_hex(HashAlgorithm::Blake2s256, b""),
+            "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9"
+        );
+        assert_eq!(
+            hash_hex(HashAlgorithm::Blake2b256, b""),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+        assert_eq!(
+            hash_hex(HashAlgorithm::Keccak, b""),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        // Substrate's `twox_256(b"")`.
+        assert_eq!(
+            hash_hex(HashAlgorithm::Twox, b""),
+            "99e9d85137db46ef4bbea33613baafd56f963c64b1f3685a4eb4abd67ff6203a"
+        );
+    }
+
+    #[test]
+    fn call_hash_uses_blake2s_256() {
+        let script_tx = b"script transaction";
+
+        assert_eq!(
+            call_hash(script_tx),
+            HashAlgorithm::Blake2s256.hash(script_tx)
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Cash limit for the pallet-move `execute` extrinsic.
+
+use anyhow::{Error, Result};
+use clap::Parser;
+use move_binary_format::file_format::{Bytecode, CompiledScript};
+use move_core_types::account_address::AccountAddress;
+use move_vm_backend_common::types::ScriptTransaction;
+use std::fmt;
+
+/// Balance-transfer functions as `(address, module, function)`, the amount is the last parameter.
+const TRANSFER_FUNCTIONS: &[(AccountAddress, &str, &str)] =
+    &[(AccountAddress::ONE, "balance", "transfer")];
+
+/// Cash limit options.
+#[derive(Parser, Debug)]
+pub(crate) struct CashLimitArgs {
+    #[clap(
+        long,
+        help = "Maximum amount of balance the script is allowed to transfer, in token units if --token-decimals is given (e.g. 1.5), otherwise in the smallest units"
+    )]
+    cash_limit: Option<String>,
+
+    #[clap(
+        long,
+        requires = "cash-limit",
+        help = "Number of the token's decimals for the --cash-limit given in token units"
+    )]
+    token_decimals: Option<u32>,
+}
+
+impl CashLimitArgs {
+    /// Returns the explicit cash limit or the one suggested for the script transaction.
+    ///
+    /// Returns `None` if the suggestion is unknown because some transfer amounts can't be determined.
+    pub(crate) fn cash_limit(&self, tx: &ScriptTransaction) -> Result<Option<u128>> {
+        match self.cash_limit {
+            Some(ref amount) => {
+                parse_token_amount(amount, self.token_decimals.unwrap_or(0)).map(Some)
+            }
+            None => {
+                let suggestion = CashLimitSuggestion::new(tx)?;
+                println!("{suggestion}");
+                Ok(suggestion.cash_limit())
+            }
+        }
+    }
+}
+
+/// Cash limit suggested by summing the amounts passed to the balance-transfer functions.
+///
+/// Only the script's direct calls to [`TRANSFER_FUNCTIONS`] are found, so transfers made inside the
+/// called modules aren't counted and the suggestion is a lower bound.
+pub(crate) struct CashLimitSuggestion {
+    /// Sum of the known amounts.
+    known_amount: u128,
+    /// Number of transfers found in the script.
+    transfers: usize,
+    /// Number of transfers with amounts that can't be determined without executing the script.
+    unknown_amounts: usize,
+}
+
+impl CashLimitSuggestion {
+    /// Analyses the script bytecode for the transfers.
+    ///
+    /// Only amounts given as constants or passed directly from the script parameters are known.
+    pub(crate) fn new(tx: &ScriptTransaction) -> Result<Self> {
+        let script = CompiledScript::deserialize(&tx.bytecode)
+            .map_err(|e| Error::msg(format!("Invalid script bytecode: {e:?}")))?;
+        let parameter_count = script.signature_at(script.parameters).len();
+
+        let mut suggestion = Self {
+            known_amount: 0,
+            transfers: 0,
+            unknown_amounts: 0,
+        };
+
+        for (i, instruction) in script.code.code.iter().enumerate() {
+            let Bytecode::Call(handle) = instruction else {
+                continue;
+            };
+
+            let function = script.function_handle_at(*handle);
+            let module = script.module_handle_at(function.module);
+            let callee = (
+                *script.address_identifier_at(module.address),
+                script.identifier_at(module.name).as_str(),
+                script.identifier_at(function.name).as_str(),
+            );
+            if !TRANSFER_FUNCTIONS.contains(&callee) {
+                continue;
+            }
+
+            suggestion.transfers += 1;
+
+            // The amount is the last argument, so it's pushed right before the call.
+            let amount = match i.checked_sub(1).map(|i| &script.code.code[i]) {
+                Some(Bytecode::LdU128(amount)) => Some(*amount),
+                Some(Bytecode::CopyLoc(idx) | Bytecode::MoveLoc(idx))
+                    if (*idx as usize) < parameter_count =>
+                {
+                    tx.args
+                        .get(*idx as usize)
+                        .and_then(|arg| bcs::from_bytes::<u128>(arg).ok())
+                }
+                _ => None,
+            };
+
+            match amount {
+                Some(amount) => {
+                    suggestion.known_amount = suggestion.known_amount.saturating_add(amount)
+                }
+                None => suggestion.unknown_amounts += 1,
+            }
+        }
+
+        Ok(suggestion)
+    }
+
+    /// Returns the suggested cash limit, or `None` if some transfer amounts are unknown.
+    pub(crate) fn cash_limit(&self) -> Option<u128> {
+        (self.unknown_amounts == 0).then_some(self.known_amount)
+    }
+}
+
+impl fmt::Display for CashLimitSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.cash_limit() {
+            Some(cash_limit) => write!(
+                f,
+                "Suggested cash_limit: at least {cash_limit} ({} direct balance transfer(s) found, transfers inside the called modules aren't counted)",
+                self.transfers
+            ),
+            None => write!(
+                f,
+                "Suggested cash_limit: unknown, the amount of {} of {} balance transfer(s) can't be determined statically, set the cash limit explicitly with --cash-limit",
+                self.unknown_amounts, self.transfers
+            ),
+        }
+    }
+}
+
+/// Parses the amount given in token units (e.g. `1.5`) into the smallest units.
+pub(crate) fn parse_token_amount(amount: &str, decimals: u32) -> Result<u128> {
+    let invalid = || Error::msg(format!("Invalid amount '{amount}'"));
+
+    let (integer, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if fraction.len() > decimals as usize {
+        return Err(Error::msg(format!(
+            "The amount '{amount}' has more than {decimals} decimals"
+        )));
+    }
+
+    let unit = 10u128.checked_pow(decimals).ok_or_else(invalid)?;
+    let integer: u128 = integer.parse().map_err(|_| invalid())?;
+    let fraction: u128 = if fraction.is_empty() {
+        0
+    } else {
+        let digits: u128 = fraction.parse().map_err(|_| invalid())?;
+        digits * 10u128.pow(decimals - fraction.len() as u32)
+    };
+
+    integer
+        .checked_mul(unit)
+        .and_then(|amount| amount.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_amount_in_smallest_units() {
+        assert_eq!(parse_token_amount("1000", 0).unwrap(), 1000);
+    }
+
+    #[test]
+    fn token_amount_with_decimals() {
+        assert_eq!(parse_token_amount("1.5", 12).unwrap(), 1_500_000_000_000);
+        assert_eq!(parse_token_amount("2", 3).unwrap(), 2000);
+        assert_eq!(parse_token_amount("0.001", 3).unwrap(), 1);
+        assert_eq!(parse_token_amount(" 3. ", 2).unwrap(), 300);
+    }
+
+    #[test]
+    fn token_amount_with_too_many_decimals() {
+        assert!(parse_token_amount("1.5", 0).is_err());
+        assert!(parse_token_amount("0.0001", 3).is_err());
+    }
+
+    #[test]
+    fn invalid_token_amount() {
+        assert!(parse_token_amount("", 0).is_err());
+        assert!(parse_token_amount("abc", 0).is_err());
+        assert!(parse_token_amount("-1", 0).is_err());
+        assert!(parse_token_amount("1.-5", 2).is_err());
+    }
+
+    #[test]
+    fn token_amount_overflowing_u128() {
+        assert!(parse_token_amount(&u128::MAX.to_string(), 1).is_err());
+        assert!(parse_token_amount("1", 39).is_err());
+    }
+}
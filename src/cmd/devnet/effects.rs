@@ -0,0 +1,87 @@
+//! Human-readable effects of an execution for reviewing them before the submission.
+
+use crate::natives::Transfer;
+use anyhow::{Error, Result};
+use move_core_types::effects::{ChangeSet, Event, Op};
+use move_core_types::resolver::ResourceResolver;
+use move_resource_viewer::MoveValueAnnotator;
+use move_vm_test_utils::InMemoryStorage;
+use std::fmt::Display;
+
+/// Indentation of the decoded values.
+const VALUE_INDENT: &str = "            ";
+
+/// Prints the resources and modules created, modified and deleted per account.
+///
+/// The storage must be the state before the changes are applied, the previous values are read
+/// from it.
+pub(crate) fn print_write_set(storage: &InMemoryStorage, changes: &ChangeSet) -> Result<()> {
+    let annotator = MoveValueAnnotator::new(storage);
+
+    println!("Write set:");
+    for (address, account) in changes.accounts() {
+        println!("    {}:", address.to_hex_literal());
+
+        for (name, op) in account.modules() {
+            println!("        {} module {name}", op_name(op));
+        }
+
+        for (tag, op) in account.resources() {
+            println!("        {} resource {tag}", op_name(op));
+
+            let before = storage
+                .get_resource(address, tag)
+                .map_err(|e| Error::msg(format!("{e:?}")))?;
+            if let Some(before) = before {
+                print_value("before", annotator.view_resource(tag, &before)?);
+            }
+            if let Op::New(after) | Op::Modify(after) = op {
+                print_value("after", annotator.view_resource(tag, after)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the emitted events.
+pub(crate) fn print_events(storage: &InMemoryStorage, events: &[Event]) -> Result<()> {
+    let annotator = MoveValueAnnotator::new(storage);
+
+    println!("Events:");
+    for (_, _, type_tag, data) in events {
+        println!("    {type_tag}");
+        print_value("data", annotator.view_value(type_tag, data)?);
+    }
+
+    Ok(())
+}
+
+/// Prints the balance transfers.
+pub(crate) fn print_transfers(transfers: &[Transfer]) {
+    for transfer in transfers {
+        println!(
+            "Transfer: {} -> {}: {}",
+            transfer.src.to_hex_literal(),
+            transfer.dst.to_hex_literal(),
+            transfer.amount
+        );
+    }
+}
+
+/// Returns the name of the operation.
+fn op_name<T>(op: &Op<T>) -> &'static str {
+    match op {
+        Op::New(_) => "Created",
+        Op::Modify(_) => "Modified",
+        Op::Delete => "Deleted",
+    }
+}
+
+/// Prints the decoded value, indented under the changed resource.
+fn print_value(label: &str, value: impl Display) {
+    println!("{VALUE_INDENT}{label}:");
+    for line in value.to_string().lines() {
+        println!("{VALUE_INDENT}    {line}");
+    }
+}
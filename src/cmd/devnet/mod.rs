@@ -0,0 +1,338 @@
+//! Local emulation of the pallet-move semantics.
+
+pub(crate) mod effects;
+pub(crate) mod storage;
+
+use crate::cmd::metadata::ArtifactMetadata;
+use crate::cmd::read_bytes;
+use crate::cmd::script_args::args::parse_address;
+use crate::local_vm::{Execution, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::natives::{balance_natives, BalanceLedger};
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use clap::{ArgGroup, Parser};
+use effects::{print_events, print_transfers, print_write_set};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::parser::parse_struct_tag;
+use move_vm_backend_common::bytecode::verify_script_integrity_and_check_signers;
+use move_vm_backend_common::types::ScriptTransaction;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use storage::DevnetState;
+
+/// Default devnet directory in the package directory.
+pub(crate) const DEFAULT_DEVNET_DIR: &str = ".devnet";
+
+/// Local pallet-move simulator.
+#[derive(Parser)]
+pub struct Devnet {
+    /// Command option.
+    #[clap(subcommand)]
+    cmd: DevnetCmd,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Directory with the devnet state (by default .devnet in the package directory)"
+    )]
+    dir: Option<PathBuf>,
+}
+
+/// List of possible devnet commands.
+#[derive(clap::Subcommand)]
+pub enum DevnetCmd {
+    /// Initialize the devnet state.
+    #[clap(about = "Initialize the devnet with the package's dependencies as the genesis modules")]
+    Init,
+
+    /// Publish a module or a bundle.
+    #[clap(about = "Publish a module or a bundle")]
+    Publish {
+        #[clap(flatten)]
+        cmd: DevnetPublish,
+    },
+
+    /// Execute a script transaction.
+    #[clap(about = "Execute a script transaction")]
+    Execute {
+        #[clap(flatten)]
+        cmd: DevnetExecute,
+    },
+
+    /// Show a resource.
+    #[clap(about = "Show a resource stored under the account")]
+    GetResource {
+        #[clap(flatten)]
+        cmd: DevnetGetResource,
+    },
+
+    /// Show or set an account's balance.
+    #[clap(about = "Show or set the account's balance")]
+    Balance {
+        #[clap(flatten)]
+        cmd: DevnetBalance,
+    },
+}
+
+impl Devnet {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let dir = self
+            .dir
+            .clone()
+            .unwrap_or_else(|| ctx.project_root_dir.join(DEFAULT_DEVNET_DIR));
+
+        match &self.cmd {
+            DevnetCmd::Init => init(ctx, &dir),
+            DevnetCmd::Publish { cmd } => cmd.execute(ctx, &mut DevnetState::open(&dir)?),
+            DevnetCmd::Execute { cmd } => cmd.execute(ctx, &mut DevnetState::open(&dir)?),
+            DevnetCmd::GetResource { cmd } => cmd.execute(&DevnetState::open(&dir)?),
+            DevnetCmd::Balance { cmd } => cmd.execute(&mut DevnetState::open(&dir)?),
+        }
+    }
+}
+
+/// Initializes the devnet state with the package's dependencies as the genesis modules.
+fn init(ctx: &RunContext, dir: &Path) -> Result<()> {
+    let mut state = DevnetState::init(dir)?;
+    state.publish_genesis_modules(&ctx.get_dependency_modules()?)?;
+
+    println!("Devnet is initialized at:\n{}", dir.display());
+
+    Ok(())
+}
+
+/// Publish a module or a bundle.
+#[derive(Parser, Debug)]
+#[clap(group(
+    ArgGroup::new("artifact")
+        .required(true)
+        .args(&["module-path", "bundle-path"]),
+))]
+pub struct DevnetPublish {
+    #[clap(short, long, help = "Path to the module (compiled by the smove)")]
+    module_path: Option<PathBuf>,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to the bundle (compiled by the smove bundle)"
+    )]
+    bundle_path: Option<PathBuf>,
+
+    #[clap(
+        short,
+        long,
+        help = "Account publishing the modules, must match the modules' address (SS58 or Move address)"
+    )]
+    sender: String,
+
+    #[clap(long, help = "Maximum gas for the publication")]
+    gas_limit: Option<u64>,
+}
+
+impl DevnetPublish {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext, state: &mut DevnetState) -> Result<()> {
+        let sender = parse_address(&self.sender)?;
+        let gas_limit = self.gas_limit.unwrap_or(ESTIMATION_GAS_LIMIT);
+        let vm = LocalVm::new(ctx)?;
+
+        let execution = match (&self.module_path, &self.bundle_path) {
+            (Some(path), _) => {
+                vm.publish_module(&state.storage, read_bytes(path)?, sender, gas_limit)?
+            }
+            (None, Some(path)) => {
+                vm.publish_bundle(&state.storage, &read_bytes(path)?, sender, gas_limit)?
+            }
+            // Use unreachable: the argument group requires one of the paths.
+            (None, None) => unreachable!(),
+        };
+
+        finish(ctx, state, &execution)
+    }
+}
+
+/// Execute a script transaction.
+#[derive(Parser, Debug)]
+pub struct DevnetExecute {
+    /// Path to the script transaction (compiled by the smove create-transaction).
+    #[clap(short, long)]
+    script_transaction_path: PathBuf,
+
+    #[clap(
+        long = "signer",
+        multiple_occurrences = true,
+        help = "Account signing the execution (SS58 or Move address), all script signers are required"
+    )]
+    signers: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Maximum amount of balance the script is allowed to transfer from each signer (by default the cash limit from the artifact's metadata)"
+    )]
+    cash_limit: Option<u128>,
+
+    #[clap(long, help = "Maximum gas for the execution")]
+    gas_limit: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Print the write set, events and transfers without persisting the changes"
+    )]
+    dry_run: bool,
+}
+
+impl DevnetExecute {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext, state: &mut DevnetState) -> Result<()> {
+        let tx: ScriptTransaction = bcs::from_bytes(&read_bytes(&self.script_transaction_path)?)
+            .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+
+        let script_signers = script_signers(&tx)?;
+        let signed = self
+            .signers
+            .iter()
+            .map(|signer| parse_address(signer))
+            .collect::<Result<Vec<_>>>()?;
+        for signer in script_signers.iter() {
+            if !signed.contains(signer) {
+                return Err(Error::msg(format!(
+                    "The script requires the signature of {}, add it with --signer",
+                    signer.to_hex_literal()
+                )));
+            }
+        }
+
+        let cash_limit = match self.cash_limit {
+            Some(cash_limit) => cash_limit,
+            None => ArtifactMetadata::load(&self.script_transaction_path)?
+                .cash_limit
+                .unwrap_or_default(),
+        };
+
+        let mut ledger = BalanceLedger {
+            balances: state.balances.clone(),
+            ..Default::default()
+        };
+        for signer in script_signers {
+            let balance = ledger.balances.get(&signer).copied().unwrap_or_default();
+            if balance < cash_limit {
+                return Err(Error::msg(format!(
+                    "Insufficient balance of {} for the cash limit {cash_limit}: {balance}",
+                    signer.to_hex_literal()
+                )));
+            }
+            ledger.cheques.insert(signer, cash_limit);
+        }
+
+        let ledger = Arc::new(Mutex::new(ledger));
+        let vm = LocalVm::with_natives(ctx, balance_natives(ledger.clone()))?;
+        let gas_limit = self.gas_limit.unwrap_or(ESTIMATION_GAS_LIMIT);
+        let execution = vm.execute_script(&state.storage, &tx, gas_limit)?;
+
+        if let Some((ref changes, ref events)) = execution.effects {
+            if self.dry_run {
+                print_write_set(&state.storage, changes)?;
+                print_events(&state.storage, events)?;
+            }
+
+            let ledger = ledger.lock().unwrap();
+            print_transfers(&ledger.transfers);
+            if !self.dry_run {
+                state.balances = ledger.balances.clone();
+                state.store_balances()?;
+            }
+        }
+
+        if self.dry_run {
+            println!("{}", execution.estimation().explained(ctx));
+            return Ok(());
+        }
+
+        finish(ctx, state, &execution)
+    }
+}
+
+/// Show a resource.
+#[derive(Parser, Debug)]
+pub struct DevnetGetResource {
+    #[clap(
+        short,
+        long,
+        help = "Account storing the resource (SS58 or Move address)"
+    )]
+    address: String,
+
+    #[clap(short, long, help = "Resource's struct tag, e.g. 0x1::Module::Struct")]
+    tag: String,
+}
+
+impl DevnetGetResource {
+    /// Executes the command.
+    pub fn execute(&self, state: &DevnetState) -> Result<()> {
+        let address = parse_address(&self.address)?;
+        let tag = parse_struct_tag(&self.tag)?;
+
+        match state.resource(&address, &tag) {
+            Some(resource) => println!("Resource {tag}: 0x{}", hex::encode(resource)),
+            None => println!(
+                "Resource {tag} not found under {}",
+                address.to_hex_literal()
+            ),
+        }
+
+        Ok(())
+    }
+}
+
+/// Show or set an account's balance.
+#[derive(Parser, Debug)]
+pub struct DevnetBalance {
+    #[clap(short, long, help = "Account (SS58 or Move address)")]
+    address: String,
+
+    #[clap(long, help = "Set the balance to the given amount")]
+    set: Option<u128>,
+}
+
+impl DevnetBalance {
+    /// Executes the command.
+    pub fn execute(&self, state: &mut DevnetState) -> Result<()> {
+        let address = parse_address(&self.address)?;
+
+        if let Some(amount) = self.set {
+            state.balances.insert(address, amount);
+            state.store_balances()?;
+        }
+
+        let balance = state.balances.get(&address).copied().unwrap_or_default();
+        println!("Balance of {}: {balance}", address.to_hex_literal());
+
+        Ok(())
+    }
+}
+
+/// Returns the script's signers, which are the first arguments of the script transaction.
+pub(crate) fn script_signers(tx: &ScriptTransaction) -> Result<Vec<AccountAddress>> {
+    let signer_count = verify_script_integrity_and_check_signers(&tx.bytecode)
+        .map_err(|e| Error::msg(format!("Script parameters verification failure {e:?}")))?;
+
+    tx.args
+        .iter()
+        .take(signer_count)
+        .map(|arg| bcs::from_bytes(arg).map_err(|e| Error::msg(format!("Invalid signer: {e}"))))
+        .collect()
+}
+
+/// Applies and persists the changes of a successful execution and prints the result.
+fn finish(ctx: &RunContext, state: &mut DevnetState, execution: &Execution) -> Result<()> {
+    execution.apply(&mut state.storage)?;
+    if let Some((ref changes, _)) = execution.effects {
+        state.commit(changes)?;
+    }
+
+    println!("{}", execution.estimation().explained(ctx));
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+//! Local devnet state persisted in a directory.
+//!
+//! The layout follows the move-cli sandbox storage:
+//! - `<DIR>/<ADDRESS>/modules/<MODULE>.mv`
+//! - `<DIR>/<ADDRESS>/resources/<STRUCT_TAG>.bcs`
+//! - `<DIR>/balances.json`
+
+use anyhow::{Context, Error, Result};
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::effects::{ChangeSet, Op};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::parser::parse_struct_tag;
+use move_vm_test_utils::InMemoryStorage;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory for the published modules of an account.
+const MODULES_DIR: &str = "modules";
+/// Directory for the resources of an account.
+const RESOURCES_DIR: &str = "resources";
+/// Extension for the module files.
+const MODULE_EXTENSION: &str = "mv";
+/// Extension for the resource files.
+const RESOURCE_EXTENSION: &str = "bcs";
+/// File with the accounts' balances.
+const BALANCES_FILE_NAME: &str = "balances.json";
+
+/// Devnet state loaded into memory.
+pub(crate) struct DevnetState {
+    /// Directory with the persisted state.
+    dir: PathBuf,
+    /// Modules and resources.
+    pub(crate) storage: InMemoryStorage,
+    /// Free balance per account.
+    pub(crate) balances: BTreeMap<AccountAddress, u128>,
+}
+
+impl DevnetState {
+    /// Creates the state directory.
+    pub(crate) fn init(dir: &Path) -> Result<Self> {
+        if dir.join(BALANCES_FILE_NAME).exists() {
+            return Err(Error::msg(format!(
+                "Devnet is already initialized at {}",
+                dir.display()
+            )));
+        }
+
+        fs::create_dir_all(dir)?;
+        let state = Self {
+            dir: dir.to_path_buf(),
+            storage: InMemoryStorage::new(),
+            balances: BTreeMap::new(),
+        };
+        state.store_balances()?;
+
+        Ok(state)
+    }
+
+    /// Loads the state from the directory.
+    pub(crate) fn open(dir: &Path) -> Result<Self> {
+        let balances_path = dir.join(BALANCES_FILE_NAME);
+        let balances = fs::read_to_string(&balances_path).with_context(|| {
+            format!(
+                "Devnet is not initialized at {}, run `smove devnet init` first",
+                dir.display()
+            )
+        })?;
+        let balances = serde_json::from_str(&balances)
+            .with_context(|| format!("Invalid balances file {}", balances_path.display()))?;
+
+        let mut storage = InMemoryStorage::new();
+        for entry in fs::read_dir(dir)? {
+            let account_dir = entry?.path();
+            let Some(address) = account_dir
+                .file_name()
+                .and_then(|name| AccountAddress::from_hex_literal(&name.to_string_lossy()).ok())
+            else {
+                continue;
+            };
+
+            for (name, blob) in read_dir_files(&account_dir.join(MODULES_DIR), MODULE_EXTENSION)? {
+                let id = ModuleId::new(address, Identifier::new(name)?);
+                storage.publish_or_overwrite_module(id, blob);
+            }
+
+            for (name, blob) in
+                read_dir_files(&account_dir.join(RESOURCES_DIR), RESOURCE_EXTENSION)?
+            {
+                storage.publish_or_overwrite_resource(address, parse_struct_tag(&name)?, blob);
+            }
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            storage,
+            balances,
+        })
+    }
+
+    /// Loads the state from the directory, creating it if it's not initialized yet.
+    pub(crate) fn open_or_init(dir: &Path) -> Result<Self> {
+        if dir.join(BALANCES_FILE_NAME).exists() {
+            Self::open(dir)
+        } else {
+            Self::init(dir)
+        }
+    }
+
+    /// Publishes the modules without verification, like the modules available at the chain's
+    /// genesis.
+    pub(crate) fn publish_genesis_modules(&mut self, module_paths: &[PathBuf]) -> Result<()> {
+        for path in module_paths {
+            let bytecode = fs::read(path)?;
+            let module = CompiledModule::deserialize(&bytecode)
+                .map_err(|e| Error::msg(format!("Invalid module {}: {e:?}", path.display())))?;
+
+            self.store_module(module.self_id(), bytecode)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stores the module without verification.
+    pub(crate) fn store_module(&mut self, id: ModuleId, bytecode: Vec<u8>) -> Result<()> {
+        write_file(
+            &self.module_path(id.address(), id.name().as_str()),
+            &bytecode,
+        )?;
+        self.storage.publish_or_overwrite_module(id, bytecode);
+
+        Ok(())
+    }
+
+    /// Stores the resource, or removes its file if the resource doesn't exist.
+    pub(crate) fn store_resource(
+        &mut self,
+        address: AccountAddress,
+        tag: StructTag,
+        blob: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let path = self.resource_path(&address, &tag);
+        match blob {
+            Some(blob) => {
+                write_file(&path, &blob)?;
+                self.storage
+                    .publish_or_overwrite_resource(address, tag, blob);
+            }
+            None if path.exists() => apply_op(&path, &Op::Delete)?,
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    /// Persists the changes already applied to the in-memory storage.
+    pub(crate) fn commit(&self, changes: &ChangeSet) -> Result<()> {
+        for (address, account) in changes.accounts() {
+            for (name, op) in account.modules() {
+                apply_op(&self.module_path(address, name.as_str()), op)?;
+            }
+            for (tag, op) in account.resources() {
+                apply_op(&self.resource_path(address, tag), op)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the resource.
+    pub(crate) fn resource(&self, address: &AccountAddress, tag: &StructTag) -> Option<Vec<u8>> {
+        fs::read(self.resource_path(address, tag)).ok()
+    }
+
+    /// Persists the balances.
+    pub(crate) fn store_balances(&self) -> Result<()> {
+        let path = self.dir.join(BALANCES_FILE_NAME);
+        fs::write(path, serde_json::to_string_pretty(&self.balances)?)?;
+
+        Ok(())
+    }
+
+    /// Path to the module file.
+    fn module_path(&self, address: &AccountAddress, name: &str) -> PathBuf {
+        self.dir
+            .join(address.to_hex_literal())
+            .join(MODULES_DIR)
+            .join(name)
+            .with_extension(MODULE_EXTENSION)
+    }
+
+    /// Path to the resource file.
+    fn resource_path(&self, address: &AccountAddress, tag: &StructTag) -> PathBuf {
+        self.dir
+            .join(address.to_hex_literal())
+            .join(RESOURCES_DIR)
+            .join(format!("{tag}.{RESOURCE_EXTENSION}"))
+    }
+}
+
+/// Writes or removes the file according to the operation.
+fn apply_op(path: &Path, op: &Op<Vec<u8>>) -> Result<()> {
+    match op {
+        Op::New(blob) | Op::Modify(blob) => write_file(path, blob),
+        Op::Delete => fs::remove_file(path)
+            .with_context(|| format!("Failure to remove filename {}", path.display())),
+    }
+}
+
+/// Writes the file, creating the parent directories.
+fn write_file(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+/// Reads all files with the extension in the directory, returns their names without the
+/// extension and their content.
+fn read_dir_files(dir: &Path, extension: &str) -> Result<Vec<(String, Vec<u8>)>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(&format!(".{extension}")))
+        else {
+            continue;
+        };
+
+        files.push((name.to_string(), fs::read(&path)?));
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::resolver::{ModuleResolver, ResourceResolver};
+
+    #[test]
+    fn state_round_trip() {
+        let dir = std::env::temp_dir().join(format!("smove-devnet-state-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let address = AccountAddress::from_hex_literal("0xCAFE").unwrap();
+        let id = ModuleId::new(address, Identifier::new("counter").unwrap());
+        let tag = parse_struct_tag("0xCAFE::counter::Counter").unwrap();
+        let removed_tag = parse_struct_tag("0xCAFE::counter::Removed").unwrap();
+
+        let mut state = DevnetState::init(&dir).unwrap();
+        state.store_module(id.clone(), vec![1, 2, 3]).unwrap();
+        state
+            .store_resource(address, tag.clone(), Some(vec![7]))
+            .unwrap();
+        state
+            .store_resource(address, removed_tag.clone(), Some(vec![8]))
+            .unwrap();
+        state
+            .store_resource(address, removed_tag.clone(), None)
+            .unwrap();
+        state.balances.insert(address, 100);
+        state.store_balances().unwrap();
+
+        assert!(dir.join("0xcafe/modules/counter.mv").exists());
+        assert!(dir
+            .join("0xcafe/resources/0xcafe::counter::Counter.bcs")
+            .exists());
+        assert!(DevnetState::init(&dir).is_err());
+
+        let state = DevnetState::open(&dir).unwrap();
+        assert_eq!(state.storage.get_module(&id).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(
+            state.storage.get_resource(&address, &tag).unwrap(),
+            Some(vec![7])
+        );
+        assert_eq!(state.resource(&address, &tag), Some(vec![7]));
+        assert_eq!(state.resource(&address, &removed_tag), None);
+        assert_eq!(state.balances, BTreeMap::from([(address, 100)]));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn uninitialized_state() {
+        let dir = std::env::temp_dir().join("smove-devnet-uninitialized");
+
+        let error = DevnetState::open(&dir).err().unwrap();
+        assert!(error.to_string().contains("run `smove devnet init` first"));
+    }
+}
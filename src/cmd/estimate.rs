@@ -0,0 +1,101 @@
+use crate::cmd::devnet::storage::DevnetState;
+use crate::cmd::node::rpc::{Estimation, EstimationArgs};
+use crate::cmd::node::NodeArgs;
+use crate::cmd::read_bytes;
+use crate::local_vm::{package_storage, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use clap::Parser;
+use jsonrpsee::rpc_params;
+use move_vm_backend_common::types::ScriptTransaction;
+use move_vm_test_utils::InMemoryStorage;
+use std::path::{Path, PathBuf};
+
+/// Estimate gas for executing a script transaction on the node or locally.
+#[derive(Parser, Debug)]
+#[clap(about = "smove estimate")]
+pub struct Estimate {
+    /// Path to the script transaction (compiled by the smove create-transaction).
+    #[clap(short, long)]
+    script_transaction_path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Estimate with the embedded MoveVM instead of the node, using the package's dependencies and bundles"
+    )]
+    offline: bool,
+
+    #[clap(
+        long,
+        requires = "offline",
+        help = "Estimate against the devnet or forked state in the directory instead of the package's builds"
+    )]
+    state: Option<PathBuf>,
+
+    #[clap(flatten)]
+    estimation_args: EstimationArgs,
+
+    /// Node access options.
+    #[clap(flatten)]
+    node_args: NodeArgs,
+}
+
+impl Estimate {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let script_tx = read_bytes(&self.script_transaction_path)?;
+
+        if self.offline {
+            let estimation = estimate_offline(ctx, &script_tx, self.state.as_deref())?;
+            return self.estimation_args.print(
+                &estimation,
+                &self.script_transaction_path,
+                None,
+                ctx,
+            );
+        }
+
+        let conn = self.node_args.connection(ctx)?;
+        let mut estimation = Estimation::request(
+            &conn,
+            "mvm_estimateGasExecuteScript",
+            rpc_params![&script_tx],
+        )?;
+        estimation.set_script_callees(ctx, &script_tx)?;
+
+        self.estimation_args
+            .print(&estimation, &self.script_transaction_path, Some(&conn), ctx)
+    }
+}
+
+/// Estimates gas for executing the script transaction with the embedded MoveVM.
+///
+/// The script is executed against the devnet state in the given directory, or against the
+/// package's builds.
+pub(crate) fn estimate_offline(
+    ctx: &RunContext,
+    script_tx: &[u8],
+    state_dir: Option<&Path>,
+) -> Result<Estimation> {
+    let tx: ScriptTransaction = bcs::from_bytes(script_tx)
+        .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+
+    let vm = LocalVm::new(ctx)?;
+    let storage = offline_storage(ctx, &vm, state_dir)?;
+    let execution = vm.execute_script(&storage, &tx, ESTIMATION_GAS_LIMIT)?;
+
+    Ok(execution.estimation())
+}
+
+/// Loads the devnet state in the given directory, or creates the storage with the package's
+/// builds.
+pub(crate) fn offline_storage(
+    ctx: &RunContext,
+    vm: &LocalVm,
+    state_dir: Option<&Path>,
+) -> Result<InMemoryStorage> {
+    match state_dir {
+        Some(dir) => Ok(DevnetState::open(dir)?.storage),
+        None => package_storage(ctx, vm),
+    }
+}
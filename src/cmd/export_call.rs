@@ -0,0 +1,172 @@
+use crate::cmd::node::submit::{execute_call, publish_bundle_call, publish_module_call};
+use crate::cmd::{read_bytes, script_args::args::HexEncodedBytes};
+use anyhow::{Error, Result};
+use clap::{ArgGroup, Parser};
+use parity_scale_codec::Decode;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use subxt::tx::TxPayload;
+use subxt::Metadata;
+
+/// Magic number prefixing the runtime metadata.
+const METADATA_MAGIC: &[u8] = b"meta";
+
+/// Exports the SCALE-encoded call data for pallet-move calls.
+#[derive(Parser, Debug)]
+#[clap(about = "Export SCALE-encoded call data for pallet-move calls")]
+#[clap(group(
+    ArgGroup::new("artifact")
+        .required(true)
+        .args(&["script-transaction-path", "module-path", "bundle-path"]),
+))]
+pub struct ExportCall {
+    #[clap(
+        long,
+        help = "Path to the runtime metadata (SCALE-encoded, as raw bytes or a hex string)"
+    )]
+    metadata_path: PathBuf,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to the script transaction (*.mvt) for the `execute` call"
+    )]
+    script_transaction_path: Option<PathBuf>,
+
+    #[clap(short, long, help = "Path to the module for the `publish_module` call")]
+    module_path: Option<PathBuf>,
+
+    #[clap(
+        short,
+        long,
+        help = "Path to the bundle (*.mvb) for the `publish_module_bundle` call"
+    )]
+    bundle_path: Option<PathBuf>,
+
+    #[clap(long, help = "Gas limit for the call")]
+    gas_limit: u64,
+
+    #[clap(
+        long,
+        help = "Maximum amount of balance the script is allowed to transfer",
+        default_value = "0"
+    )]
+    cash_limit: u128,
+}
+
+impl ExportCall {
+    /// Executes the command.
+    pub fn execute(&self) -> Result<()> {
+        let metadata = read_metadata(&self.metadata_path)?;
+
+        let call = if let Some(ref path) = self.script_transaction_path {
+            execute_call(read_bytes(path)?, self.gas_limit, self.cash_limit)
+        } else if let Some(ref path) = self.module_path {
+            publish_module_call(read_bytes(path)?, self.gas_limit)
+        } else if let Some(ref path) = self.bundle_path {
+            publish_bundle_call(read_bytes(path)?, self.gas_limit)
+        } else {
+            unreachable!("clap requires one of the artifacts")
+        };
+
+        let call_data = call
+            .encode_call_data(&metadata)
+            .map_err(|e| Error::msg(format!("Can't encode the call data: {e}")))?;
+
+        // The first two bytes are the pallet and the call indices.
+        println!("Pallet index: {}", call_data[0]);
+        println!("Call index: {}", call_data[1]);
+        println!("Call data: {}", HexEncodedBytes::from(call_data));
+
+        Ok(())
+    }
+}
+
+/// Reads the runtime metadata from a file containing either raw bytes or a hex string.
+fn read_metadata(path: &Path) -> Result<Metadata> {
+    let bytes = read_bytes(path)?;
+
+    // Raw metadata starts with the magic number, otherwise it's expected to be a hex string
+    // returned by the `state_getMetadata` RPC call.
+    let bytes = if bytes.starts_with(METADATA_MAGIC) {
+        bytes
+    } else {
+        let hex = String::from_utf8(bytes).map_err(Error::msg)?;
+        HexEncodedBytes::from_str(hex.trim())?.into_inner()
+    };
+
+    Metadata::decode(&mut &bytes[..]).map_err(|e| {
+        Error::msg(format!(
+            "Can't decode the metadata from {}: {e}",
+            path.display()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::node::submit::PALLET_NAME;
+    use std::fs;
+    use subxt::tx::DynamicPayload;
+
+    /// Runtime metadata with pallet-move at the index 42.
+    const METADATA: &[u8] = include_bytes!("../../tests/fixtures/metadata.scale");
+
+    /// Writes the metadata file for the test.
+    fn metadata_file(name: &str, content: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("smove-{name}-{}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn raw_and_hex_metadata() {
+        let raw = metadata_file("raw-metadata", METADATA);
+        let hex = metadata_file(
+            "hex-metadata",
+            format!("0x{}\n", hex::encode(METADATA)).as_bytes(),
+        );
+
+        for path in [raw, hex] {
+            let metadata = read_metadata(&path).unwrap();
+            assert_eq!(metadata.pallet_by_name(PALLET_NAME).unwrap().index(), 42);
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid_metadata() {
+        let corrupted = metadata_file("corrupted-metadata", &[METADATA_MAGIC, &[0xff; 4]].concat());
+        let error = read_metadata(&corrupted).unwrap_err().to_string();
+        assert!(error.starts_with("Can't decode the metadata"), "{error}");
+
+        let not_hex = metadata_file("not-hex-metadata", b"metadata");
+        assert!(read_metadata(&not_hex).is_err());
+
+        fs::remove_file(corrupted).unwrap();
+        fs::remove_file(not_hex).unwrap();
+    }
+
+    #[test]
+    fn pallet_and_call_indices() {
+        let metadata = Metadata::decode(&mut &METADATA[..]).unwrap();
+        let call_data = |call: DynamicPayload| call.encode_call_data(&metadata).unwrap();
+
+        // Pallet and call indices, the compact length of the artifact, the artifact, the gas
+        // limit and the cash limit.
+        let execute = call_data(execute_call(vec![1, 2, 3], 500, 7));
+        let mut expected = vec![42, 0, 12, 1, 2, 3];
+        expected.extend(500u64.to_le_bytes());
+        expected.extend(7u128.to_le_bytes());
+        assert_eq!(execute, expected);
+
+        let publish_module = call_data(publish_module_call(vec![1, 2, 3], 500));
+        assert_eq!(publish_module[..2], [42, 1]);
+        assert_eq!(publish_module[6..], 500u64.to_le_bytes());
+
+        let publish_bundle = call_data(publish_bundle_call(vec![1, 2, 3], 500));
+        assert_eq!(publish_bundle[..2], [42, 2]);
+        assert_eq!(publish_bundle[6..], 500u64.to_le_bytes());
+    }
+}
@@ -0,0 +1,354 @@
+//! Fork the on-chain state of accounts into the local devnet storage.
+//!
+//! The pallet-move RPC methods can't list an account's modules and resources, so the fetched
+//! items are discovered from the package and its dependencies: the modules published under the
+//! forked accounts, then every non-generic resource type they define, probed under every forked
+//! account.
+
+use crate::cmd::devnet::storage::DevnetState;
+use crate::cmd::devnet::DEFAULT_DEVNET_DIR;
+use crate::cmd::node::balance::free_balance;
+use crate::cmd::node::NodeArgs;
+use crate::cmd::script_args::args::{parse_address, ss58_address};
+use crate::run_context::RunContext;
+use anyhow::{Context, Error, Result};
+use clap::Parser;
+use jsonrpsee::rpc_params;
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::parser::parse_struct_tag;
+use move_core_types::resolver::ModuleResolver;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use subxt::utils::{AccountId32, H256};
+
+/// File in the devnet directory with the block hashes the items were fetched at.
+const FORK_CACHE_FILE_NAME: &str = "fork.json";
+
+/// Fork the on-chain state of accounts into the local devnet storage.
+#[derive(Parser, Debug)]
+#[clap(about = "smove fork")]
+pub struct Fork {
+    #[clap(
+        long,
+        required = true,
+        use_value_delimiter = true,
+        help = "Comma-separated list of accounts to fork (SS58 or Move addresses)"
+    )]
+    accounts: Vec<String>,
+
+    #[clap(
+        long = "module",
+        multiple_occurrences = true,
+        help = "Additional module to fetch in the `<ADDRESS>::<NAME>` format, can be repeated"
+    )]
+    modules: Vec<String>,
+
+    #[clap(
+        long = "resource",
+        multiple_occurrences = true,
+        help = "Additional resource type to fetch from every account, e.g. a generic instantiation, can be repeated"
+    )]
+    resources: Vec<String>,
+
+    #[clap(
+        long,
+        help = "Block hash to fork at (by default the best block), the cached items are reused only when forking at the same block again"
+    )]
+    at: Option<String>,
+
+    #[clap(
+        long,
+        help = "Directory with the devnet state (by default .devnet in the package directory)"
+    )]
+    dir: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Fetch all items again, even those cached at the same block (only useful with --at, as the best block changes)"
+    )]
+    refresh: bool,
+
+    /// Node access options.
+    #[clap(flatten)]
+    node_args: NodeArgs,
+}
+
+/// Block hashes the items were fetched at.
+#[derive(Default, Serialize, Deserialize)]
+struct ForkCache {
+    /// Node the items were fetched from.
+    url: Option<String>,
+    /// Fetched items by their description, e.g. `module 0x1::signer`.
+    items: BTreeMap<String, CachedItem>,
+}
+
+/// Fetched item.
+#[derive(Serialize, Deserialize)]
+struct CachedItem {
+    /// Block hash the item was fetched at.
+    block_hash: H256,
+    /// Whether the item exists at the block.
+    found: bool,
+}
+
+impl ForkCache {
+    /// Loads the cache for the node, or creates an empty one.
+    ///
+    /// The items fetched from another node are dropped, as the block hashes may match across
+    /// nodes of different chains (e.g. the genesis of dev chains).
+    fn load(path: &Path, url: &str) -> Result<Self> {
+        let mut cache = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)
+                .with_context(|| format!("Invalid fork cache {}", path.display()))?
+        } else {
+            Self::default()
+        };
+
+        if cache.url.as_deref() != Some(url) {
+            cache.url = Some(url.to_string());
+            cache.items.clear();
+        }
+
+        Ok(cache)
+    }
+
+    /// Stores the cache.
+    fn store(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Checks if the item was already fetched at the block.
+    fn is_cached(&self, item: &str, block_hash: H256) -> bool {
+        self.items
+            .get(item)
+            .is_some_and(|cached| cached.block_hash == block_hash)
+    }
+}
+
+impl Fork {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let dir = self
+            .dir
+            .clone()
+            .unwrap_or_else(|| ctx.project_root_dir.join(DEFAULT_DEVNET_DIR));
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|account| parse_address(account))
+            .collect::<Result<BTreeSet<_>>>()?;
+        let extra_tags = self
+            .resources
+            .iter()
+            .map(|tag| parse_struct_tag(tag))
+            .collect::<Result<Vec<_>>>()?;
+
+        let local_modules = ctx.get_compiled_modules()?;
+        let mut module_ids = local_modules
+            .iter()
+            .map(|module| module.self_id())
+            .filter(|id| accounts.contains(id.address()))
+            .collect::<BTreeSet<_>>();
+        for module in self.modules.iter() {
+            module_ids.insert(parse_module_id(module)?);
+        }
+
+        let conn = self.node_args.connection(ctx)?;
+        conn.check_websocket()?;
+        let mut state = DevnetState::open_or_init(&dir)?;
+        let cache_path = dir.join(FORK_CACHE_FILE_NAME);
+        let mut cache = ForkCache::load(&cache_path, conn.url.as_str())?;
+
+        let (mut found, mut fetched, mut cached) = (0, 0, 0);
+        conn.block_on(async {
+            let client = conn.client().await?;
+            let block_hash: H256 = match self.at {
+                Some(ref hash) => H256::from_str(hash)
+                    .map_err(|e| Error::msg(format!("Invalid block hash '{hash}': {e}")))?,
+                None => client.request("chain_getBlockHash", rpc_params![]).await?,
+            };
+            println!("Forking {} at the block {block_hash:?}", conn.url);
+
+            let mut modules = local_modules;
+            for id in module_ids {
+                let item = format!("module {}::{}", id.address().to_hex_literal(), id.name());
+                let bytecode = if !self.refresh && cache.is_cached(&item, block_hash) {
+                    cached += 1;
+                    state
+                        .storage
+                        .get_module(&id)
+                        .map_err(|e| Error::msg(format!("{e:?}")))?
+                } else {
+                    fetched += 1;
+                    let params =
+                        rpc_params![ss58_address(id.address()), id.name().as_str(), block_hash];
+                    let bytecode: Option<Vec<u8>> = client.request("mvm_getModule", params).await?;
+                    if let Some(ref bytecode) = bytecode {
+                        state.store_module(id.clone(), bytecode.clone())?;
+                    }
+                    cache.items.insert(
+                        item,
+                        CachedItem {
+                            block_hash,
+                            found: bytecode.is_some(),
+                        },
+                    );
+                    bytecode
+                };
+
+                if let Some(bytecode) = bytecode {
+                    found += 1;
+                    modules.push(CompiledModule::deserialize(&bytecode).map_err(|e| {
+                        Error::msg(format!("Invalid module {id} fetched from the node: {e:?}"))
+                    })?);
+                }
+            }
+
+            let tags = resource_tags(&modules).into_iter().chain(extra_tags);
+            let tags = tags.collect::<BTreeSet<_>>();
+            for account in accounts.iter() {
+                for tag in tags.iter() {
+                    let item = format!("resource {} {tag}", account.to_hex_literal());
+                    if !self.refresh && cache.is_cached(&item, block_hash) {
+                        cached += 1;
+                        found += usize::from(cache.items[&item].found);
+                        continue;
+                    }
+
+                    fetched += 1;
+                    let params =
+                        rpc_params![ss58_address(account), bcs::to_bytes(tag)?, block_hash];
+                    let blob: Option<Vec<u8>> = client.request("mvm_getResource", params).await?;
+                    found += usize::from(blob.is_some());
+                    cache.items.insert(
+                        item,
+                        CachedItem {
+                            block_hash,
+                            found: blob.is_some(),
+                        },
+                    );
+                    state.store_resource(*account, tag.clone(), blob)?;
+                }
+            }
+
+            let api = conn.subxt_client().await?;
+            for account in accounts.iter() {
+                let balance = free_balance(
+                    &api,
+                    AccountId32::from(account.into_bytes()),
+                    Some(block_hash),
+                )
+                .await?;
+                state.balances.insert(*account, balance);
+            }
+
+            Ok::<_, Error>(())
+        })?;
+
+        state.store_balances()?;
+        cache.store(&cache_path)?;
+
+        println!(
+            "Forked {found} modules and resources into {} ({fetched} fetched, {cached} cached)",
+            dir.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Returns the non-generic resource types defined by the modules.
+fn resource_tags(modules: &[CompiledModule]) -> Vec<StructTag> {
+    let mut tags = vec![];
+    for module in modules {
+        for def in module.struct_defs() {
+            let handle = module.struct_handle_at(def.struct_handle);
+            if !handle.abilities.has_key() || !handle.type_parameters.is_empty() {
+                continue;
+            }
+
+            tags.push(StructTag {
+                address: *module.address(),
+                module: module.name().to_owned(),
+                name: module.identifier_at(handle.name).to_owned(),
+                type_params: vec![],
+            });
+        }
+    }
+
+    tags
+}
+
+/// Parses the module ID in the `<ADDRESS>::<NAME>` format.
+fn parse_module_id(s: &str) -> Result<ModuleId> {
+    let (address, name) = s.split_once("::").ok_or(Error::msg(format!(
+        "Invalid module '{s}', expected the `<ADDRESS>::<NAME>` format"
+    )))?;
+
+    Ok(ModuleId::new(
+        parse_address(address)?,
+        Identifier::new(name)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEM: &str = "module 0x1::signer";
+
+    fn cache(block_hash: H256) -> ForkCache {
+        ForkCache {
+            url: Some("ws://localhost:9944/".to_string()),
+            items: BTreeMap::from([(
+                ITEM.to_string(),
+                CachedItem {
+                    block_hash,
+                    found: true,
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn item_is_cached_at_the_same_block() {
+        let block_hash = H256::repeat_byte(1);
+        let cache = cache(block_hash);
+
+        assert!(cache.is_cached(ITEM, block_hash));
+        assert!(!cache.is_cached(ITEM, H256::repeat_byte(2)));
+        assert!(!cache.is_cached("module 0x1::vector", block_hash));
+    }
+
+    #[test]
+    fn cache_of_another_node_is_dropped() {
+        let path =
+            std::env::temp_dir().join(format!("smove-fork-cache-{}.json", std::process::id()));
+        cache(H256::repeat_byte(1)).store(&path).unwrap();
+
+        let same_node = ForkCache::load(&path, "ws://localhost:9944/").unwrap();
+        let other_node = ForkCache::load(&path, "ws://localhost:9945/").unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(same_node.items.len(), 1);
+        assert!(other_node.items.is_empty());
+        assert_eq!(other_node.url.as_deref(), Some("ws://localhost:9945/"));
+    }
+
+    #[test]
+    fn module_id_format() {
+        let id = parse_module_id("0x1::signer").unwrap();
+
+        assert_eq!(id.address(), &AccountAddress::ONE);
+        assert_eq!(id.name().as_str(), "signer");
+        assert!(parse_module_id("0x1").is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::gas_schedule::{self, GasSchedule as Schedule};
+use anyhow::Result;
+use clap::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Gas schedule commands.
+///
+/// pallet-move compiles the gas schedule into the runtime instead of keeping it in the storage or
+/// the pallet's constants, and provides no RPC method returning it, so the schedule can't be
+/// fetched from the node. The built-in schedule is the one pallet-move uses.
+#[derive(clap::Subcommand)]
+pub enum GasSchedule {
+    /// Export the built-in gas schedule.
+    #[clap(about = "Export the built-in gas schedule, which pallet-move uses")]
+    Export {
+        #[clap(flatten)]
+        cmd: ExportGasSchedule,
+    },
+}
+
+impl GasSchedule {
+    /// Executes the command.
+    pub fn execute(&self) -> Result<()> {
+        match self {
+            Self::Export { cmd } => cmd.execute(),
+        }
+    }
+}
+
+/// Export the built-in gas schedule.
+#[derive(Parser, Debug)]
+pub struct ExportGasSchedule {
+    #[clap(
+        short,
+        long,
+        help = "Output file, TOML for the .toml extension and JSON otherwise (by default printed as JSON)"
+    )]
+    output: Option<PathBuf>,
+}
+
+impl ExportGasSchedule {
+    /// Executes the command.
+    pub fn execute(&self) -> Result<()> {
+        write_schedule(&Schedule::built_in(), self.output.as_deref())
+    }
+}
+
+/// Writes the gas schedule to the file or prints it.
+fn write_schedule(schedule: &Schedule, output: Option<&Path>) -> Result<()> {
+    let content = gas_schedule::to_string(schedule, output)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, content)?;
+            println!("Gas schedule is written to:\n{}", path.display());
+        }
+        None => println!("{content}"),
+    }
+
+    Ok(())
+}
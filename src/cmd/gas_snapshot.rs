@@ -0,0 +1,238 @@
+use crate::cmd::node::rpc::estimate_all::{estimate_all, estimate_all_offline};
+use crate::cmd::node::NodeArgs;
+use crate::run_context::RunContext;
+use anyhow::{Context, Error, Result};
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the gas snapshot file in the package directory.
+const GAS_SNAPSHOT_FILE_NAME: &str = ".gas-snapshot";
+
+/// Stores or checks the estimated gas of all script transactions and bundles.
+#[derive(Parser, Debug)]
+#[clap(about = "smove gas-snapshot")]
+pub struct GasSnapshot {
+    #[clap(
+        long,
+        help = "Compare the estimations with the stored snapshot instead of updating it"
+    )]
+    check: bool,
+
+    #[clap(
+        long,
+        help = "Allowed gas increase in percent when checking the snapshot",
+        default_value = "0"
+    )]
+    tolerance: f64,
+
+    #[clap(
+        short,
+        long,
+        help = "Account ID in the SS58 format for publishing bundles (by default the network's account ID, \
+        or the bundle's address with --offline)"
+    )]
+    account_id: Option<String>,
+
+    #[clap(
+        long,
+        help = "Estimate with the embedded MoveVM instead of the node, using the package's dependencies and bundles"
+    )]
+    offline: bool,
+
+    #[clap(
+        long,
+        requires = "offline",
+        help = "Estimate against the devnet or forked state in the directory instead of the package's builds"
+    )]
+    state: Option<PathBuf>,
+
+    /// Node access options.
+    #[clap(flatten)]
+    node_args: NodeArgs,
+}
+
+/// Estimated gas per artifact name.
+type Snapshot = BTreeMap<String, u64>;
+
+impl GasSnapshot {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let snapshot_path = ctx.project_root_dir.join(GAS_SNAPSHOT_FILE_NAME);
+        let results = if self.offline {
+            estimate_all_offline(ctx, self.account_id.as_ref(), self.state.as_deref())?
+        } else {
+            let conn = self.node_args.connection(ctx)?;
+            estimate_all(&conn, ctx, self.account_id.as_ref())?
+        };
+
+        let mut snapshot = Snapshot::new();
+        for result in results {
+            // Use unwrap: artifacts are found by their extension, so they have a file name.
+            let name = result
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let estimation = result
+                .estimation
+                .with_context(|| format!("Gas estimation failed for {name}"))?;
+
+            if !estimation.is_executed() {
+                return Err(Error::msg(format!(
+                    "Gas estimation failed for {name} with the status code {:?}",
+                    estimation.vm_status_code
+                )));
+            }
+
+            snapshot.insert(name, estimation.gas_used);
+        }
+
+        if self.check {
+            let stored = read_snapshot(&snapshot_path)?;
+            check_snapshot(&stored, &snapshot, self.tolerance)
+        } else {
+            write_snapshot(&snapshot_path, &snapshot)?;
+            println!("Gas snapshot is stored at:\n{}", snapshot_path.display());
+            Ok(())
+        }
+    }
+}
+
+/// Compares the new snapshot with the stored one and fails if any gas usage grew beyond the
+/// tolerance.
+fn check_snapshot(stored: &Snapshot, snapshot: &Snapshot, tolerance: f64) -> Result<()> {
+    let mut regressions = 0;
+
+    for (name, gas) in snapshot.iter() {
+        let Some(stored_gas) = stored.get(name) else {
+            println!("{name}: {gas} (new, not in the snapshot)");
+            continue;
+        };
+
+        let change = if *stored_gas == 0 {
+            0.0
+        } else {
+            (*gas as f64 - *stored_gas as f64) * 100.0 / *stored_gas as f64
+        };
+
+        let regression = gas > stored_gas && (*stored_gas == 0 || change > tolerance);
+        if regression {
+            regressions += 1;
+        }
+
+        println!(
+            "{name}: {stored_gas} -> {gas} ({change:+.2}%){}",
+            if regression { " REGRESSION" } else { "" }
+        );
+    }
+
+    for name in stored.keys().filter(|name| !snapshot.contains_key(*name)) {
+        println!("{name}: removed, not found in the package");
+    }
+
+    if regressions > 0 {
+        return Err(Error::msg(format!(
+            "Gas usage grew beyond the {tolerance}% tolerance for {regressions} artifact(s)"
+        )));
+    }
+
+    println!("Gas snapshot check passed");
+
+    Ok(())
+}
+
+/// Reads the snapshot file with lines in the `<ARTIFACT> <GAS>` format.
+fn read_snapshot(path: &Path) -> Result<Snapshot> {
+    let content = fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failure to read the gas snapshot {}, create it with `smove gas-snapshot`",
+            path.display()
+        )
+    })?;
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, gas) = line
+                .rsplit_once(' ')
+                .with_context(|| format!("Invalid gas snapshot line '{line}'"))?;
+            let gas = gas
+                .parse()
+                .with_context(|| format!("Invalid gas in the snapshot line '{line}'"))?;
+            Ok((name.to_string(), gas))
+        })
+        .collect()
+}
+
+/// Writes the snapshot file with lines in the `<ARTIFACT> <GAS>` format.
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    let content = snapshot
+        .iter()
+        .map(|(name, gas)| format!("{name} {gas}\n"))
+        .collect::<String>();
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, u64)]) -> Snapshot {
+        entries
+            .iter()
+            .map(|(name, gas)| (name.to_string(), *gas))
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_or_lower_gas_passes() {
+        let stored = snapshot(&[("a.mvt", 100), ("b.mvb", 200)]);
+        let current = snapshot(&[("a.mvt", 100), ("b.mvb", 150)]);
+
+        assert!(check_snapshot(&stored, &current, 0.0).is_ok());
+    }
+
+    #[test]
+    fn gas_growth_within_tolerance_passes() {
+        let stored = snapshot(&[("a.mvt", 100)]);
+        let current = snapshot(&[("a.mvt", 105)]);
+
+        assert!(check_snapshot(&stored, &current, 5.0).is_ok());
+        assert!(check_snapshot(&stored, &current, 4.9).is_err());
+    }
+
+    #[test]
+    fn gas_growth_from_zero_is_a_regression() {
+        let stored = snapshot(&[("a.mvt", 0)]);
+        let current = snapshot(&[("a.mvt", 1)]);
+
+        assert!(check_snapshot(&stored, &current, 100.0).is_err());
+    }
+
+    #[test]
+    fn new_and_removed_artifacts_pass() {
+        let stored = snapshot(&[("a.mvt", 100)]);
+        let current = snapshot(&[("b.mvt", 1000)]);
+
+        assert!(check_snapshot(&stored, &current, 0.0).is_ok());
+    }
+
+    #[test]
+    fn snapshot_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("smove-gas-snapshot-{}", std::process::id()));
+        let stored = snapshot(&[("initial coin minting.mvt", 100), ("b.mvb", 200)]);
+
+        write_snapshot(&path, &stored).unwrap();
+        let read = read_snapshot(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.unwrap(), stored);
+    }
+}
@@ -0,0 +1,80 @@
+//! Metadata files stored next to the generated artifacts.
+
+use anyhow::{Context, Result};
+use blake2::{Blake2s256, Digest};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Submission related metadata for an artifact, stored as `<ARTIFACT>.json` (e.g. `script.mvt.json`).
+///
+/// The limits are bound to the artifact's hash, so they're ignored once the artifact is rebuilt.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ArtifactMetadata {
+    /// Blake2s-256 hash of the artifact the limits were computed for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artifact_hash: Option<String>,
+    /// Recommended gas limit for the extrinsic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) gas_limit: Option<u64>,
+    /// Maximum amount of balance the script is allowed to transfer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cash_limit: Option<u128>,
+    /// Fields written by other tools are preserved.
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ArtifactMetadata {
+    /// Loads the metadata for the artifact or returns empty metadata if there is none.
+    ///
+    /// The limits stored for a different version of the artifact are dropped with a warning.
+    pub(crate) fn load(artifact_path: &Path) -> Result<Self> {
+        let path = metadata_path(artifact_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failure to read filename {}", path.display()))?;
+        let mut metadata: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid metadata file {}", path.display()))?;
+
+        let has_limits = metadata.gas_limit.is_some() || metadata.cash_limit.is_some();
+        if has_limits && metadata.artifact_hash != Some(artifact_hash(artifact_path)?) {
+            eprintln!(
+                "Warning: ignoring the limits in {}, the artifact changed since they were written",
+                path.display()
+            );
+            metadata.gas_limit = None;
+            metadata.cash_limit = None;
+        }
+
+        Ok(metadata)
+    }
+
+    /// Stores the metadata next to the artifact and returns the path to the metadata file.
+    pub(crate) fn store(&mut self, artifact_path: &Path) -> Result<PathBuf> {
+        self.artifact_hash = Some(artifact_hash(artifact_path)?);
+
+        let path = metadata_path(artifact_path);
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(path)
+    }
+}
+
+/// Returns the hex encoded hash of the artifact's content.
+fn artifact_hash(artifact_path: &Path) -> Result<String> {
+    let artifact = fs::read(artifact_path)
+        .with_context(|| format!("Failure to read filename {}", artifact_path.display()))?;
+
+    Ok(hex::encode(Blake2s256::digest(artifact)))
+}
+
+/// Returns the metadata file path for the artifact.
+fn metadata_path(artifact_path: &Path) -> PathBuf {
+    let mut path = artifact_path.as_os_str().to_owned();
+    path.push(".json");
+    PathBuf::from(path)
+}
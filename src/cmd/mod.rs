@@ -2,7 +2,19 @@
 
 pub(super) mod bundle;
 pub(super) mod call_hash;
+pub(super) mod cash_limit;
+pub(super) mod devnet;
+pub(super) mod estimate;
+pub(super) mod export_call;
+pub(super) mod fork;
+pub(super) mod gas_schedule;
+pub(super) mod gas_snapshot;
+pub(super) mod metadata;
+pub(super) mod multisig;
 pub(super) mod node;
+pub(super) mod profile;
+pub(super) mod sandbox;
+pub(super) mod scenario;
 pub(super) mod script;
 pub(super) mod script_args;
 
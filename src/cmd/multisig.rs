@@ -0,0 +1,338 @@
+//! Multisig script execution workflow.
+
+use crate::cmd::call_hash::call_hash;
+use crate::cmd::node::{submit::PALLET_NAME, NodeArgs};
+use crate::cmd::{read_bytes, script_args::args::HexEncodedBytes};
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use clap::Parser;
+use move_vm_backend_common::bytecode::verify_script_integrity_and_check_signers;
+use move_vm_backend_common::types::ScriptTransaction;
+use std::path::PathBuf;
+use subxt::dynamic::Value;
+use subxt::ext::scale_value::{self, At, Composite, Primitive, ValueDef};
+use subxt::utils::AccountId32;
+
+/// Storage of the pallet-move multisig requests, mapped by the call hash.
+const MULTISIG_STORAGE: &str = "MultisigStorage";
+/// Constant defining for how many blocks the multisig request is kept.
+const MAX_LIFETIME_REQUESTS: &str = "MaxLifetimeRequests";
+/// Signature status stored by the pallet for the signers who approved the execution.
+const APPROVED: &str = "Approved";
+
+/// Multisig script execution commands.
+#[derive(clap::Subcommand)]
+pub enum Multisig {
+    /// Show the signing status of a multisig script transaction.
+    #[clap(
+        about = "Show who has signed the script transaction, who is pending and when it expires"
+    )]
+    Status {
+        #[clap(flatten)]
+        cmd: MultisigStatus,
+    },
+}
+
+impl Multisig {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        match self {
+            Self::Status { cmd } => cmd.execute(ctx),
+        }
+    }
+}
+
+/// Show the signing status of a multisig script transaction.
+#[derive(Parser, Debug)]
+#[clap(about = "smove multisig status")]
+pub struct MultisigStatus {
+    /// Path to the script transaction (compiled by the smove create-transaction).
+    #[clap(short, long = "script-transaction")]
+    script_transaction_path: PathBuf,
+
+    /// Node access options.
+    #[clap(flatten)]
+    node_args: NodeArgs,
+}
+
+/// Signing status of a single signer.
+struct SignerStatus {
+    /// Signer's account.
+    account: AccountId32,
+    /// Status stored by the pallet, `None` if there is no request on-chain yet.
+    status: Option<String>,
+}
+
+impl MultisigStatus {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let script_tx = read_bytes(&self.script_transaction_path)?;
+        let signers = required_signers(&script_tx)?;
+        let call_hash = call_hash(&script_tx);
+
+        println!("Call hash: {}", HexEncodedBytes::from(call_hash));
+
+        if signers.len() < 2 {
+            println!(
+                "The script requires {} signer(s), it's executed without the multisig workflow",
+                signers.len()
+            );
+            return Ok(());
+        }
+
+        let conn = self.node_args.connection(ctx)?;
+        let (request, lifetime, block) = conn.block_on(async {
+            let api = conn.subxt_client().await?;
+            let storage = api.storage().at_latest().await?;
+
+            let query = subxt::dynamic::storage(
+                PALLET_NAME,
+                MULTISIG_STORAGE,
+                vec![Value::from_bytes(call_hash)],
+            );
+            let request = match storage.fetch(&query).await? {
+                Some(request) => Some(request.to_value()?),
+                None => None,
+            };
+
+            let lifetime = api
+                .constants()
+                .at(&subxt::dynamic::constant(
+                    PALLET_NAME,
+                    MAX_LIFETIME_REQUESTS,
+                ))
+                .ok()
+                .and_then(|lifetime| lifetime.to_value().ok())
+                .and_then(|lifetime| lifetime.as_u128());
+
+            let block = api.blocks().at_latest().await?.number();
+
+            Ok::<_, Error>((request, lifetime, block))
+        })?;
+
+        let Some(request) = request else {
+            println!("Not signed yet, the request is created on-chain by the first signature");
+            print_signers(signers.into_iter().map(|account| SignerStatus {
+                account,
+                status: None,
+            }));
+            return Ok(());
+        };
+
+        let signatures = request.at("signatures").ok_or(Error::msg(
+            "Unexpected multisig request layout returned by the node",
+        ))?;
+        let statuses = signers.into_iter().map(|account| SignerStatus {
+            status: signature_status(signatures, &account),
+            account,
+        });
+        print_signers(statuses);
+
+        let created = request.at("block_height").and_then(|block| block.as_u128());
+        match (created, lifetime) {
+            (Some(created), Some(lifetime)) => {
+                let expires = created + lifetime;
+                let remaining = expires.saturating_sub(block.into());
+                println!("Expires at block {expires} ({remaining} blocks remaining)");
+            }
+            _ => println!("Expiration is unknown, the node doesn't provide it"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the signers of the script, which are the first arguments of the script transaction.
+fn required_signers(script_tx: &[u8]) -> Result<Vec<AccountId32>> {
+    let tx: ScriptTransaction = bcs::from_bytes(script_tx)
+        .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+
+    let signer_count = verify_script_integrity_and_check_signers(&tx.bytecode)
+        .map_err(|e| Error::msg(format!("Script parameters verification failure {e:?}")))?;
+
+    tx.args
+        .iter()
+        .take(signer_count)
+        .map(|arg| {
+            let address: [u8; 32] = bcs::from_bytes(arg)
+                .map_err(|e| Error::msg(format!("Invalid signer argument: {e}")))?;
+            Ok(AccountId32::from(address))
+        })
+        .collect()
+}
+
+/// Finds the signer's status in the signatures map, stored as a sequence of `(account, data)`.
+fn signature_status<T>(
+    signatures: &scale_value::Value<T>,
+    account: &AccountId32,
+) -> Option<String> {
+    let ValueDef::Composite(ref entries) = signatures.value else {
+        return None;
+    };
+
+    entries.values().find_map(|entry| {
+        let key = entry.at(0)?;
+        if bytes(key)? != account.0 {
+            return None;
+        }
+
+        match entry.at(1)?.at("signature")?.value {
+            ValueDef::Variant(ref variant) => Some(variant.name.clone()),
+            _ => Some("Unknown".to_string()),
+        }
+    })
+}
+
+/// Collects the bytes of an account ID value, which may be nested in a composite.
+///
+/// Returns `None` if the value contains anything else than bytes.
+fn bytes<T>(value: &scale_value::Value<T>) -> Option<Vec<u8>> {
+    match value.value {
+        ValueDef::Primitive(Primitive::U128(byte)) => {
+            u8::try_from(byte).ok().map(|byte| vec![byte])
+        }
+        ValueDef::Composite(Composite::Named(ref fields)) => fields
+            .iter()
+            .map(|(_, value)| bytes(value))
+            .collect::<Option<Vec<_>>>()
+            .map(|bytes| bytes.concat()),
+        ValueDef::Composite(Composite::Unnamed(ref values)) => values
+            .iter()
+            .map(bytes)
+            .collect::<Option<Vec<_>>>()
+            .map(|bytes| bytes.concat()),
+        _ => None,
+    }
+}
+
+/// Prints the signers and their status.
+fn print_signers(statuses: impl Iterator<Item = SignerStatus>) {
+    let (mut signed, mut pending) = (0, 0);
+
+    for SignerStatus { account, status } in statuses {
+        match status.as_deref() {
+            Some(APPROVED) => {
+                signed += 1;
+                println!("    {account}: signed");
+            }
+            status => {
+                pending += 1;
+                println!(
+                    "    {account}: {}",
+                    status.unwrap_or("pending").to_lowercase()
+                );
+            }
+        }
+    }
+
+    println!("Signed: {signed}, pending: {pending}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::{
+        empty_script, Signature, SignatureIndex, SignatureToken,
+    };
+    use move_core_types::account_address::AccountAddress;
+
+    /// Builds the script transaction with the signer parameters followed by a `u64` parameter.
+    fn script_transaction(signers: &[AccountAddress]) -> Vec<u8> {
+        let mut script = empty_script();
+        let mut parameters = vec![SignatureToken::Signer; signers.len()];
+        parameters.push(SignatureToken::U64);
+        script.signatures.push(Signature(parameters));
+        script.parameters = SignatureIndex(1);
+
+        let mut bytecode = vec![];
+        script.serialize(&mut bytecode).unwrap();
+
+        let mut args: Vec<_> = signers
+            .iter()
+            .map(|signer| bcs::to_bytes(signer).unwrap())
+            .collect();
+        args.push(bcs::to_bytes(&7u64).unwrap());
+
+        bcs::to_bytes(&ScriptTransaction {
+            bytecode,
+            args,
+            type_args: vec![],
+        })
+        .unwrap()
+    }
+
+    /// Builds the signatures map of the multisig request as decoded by subxt.
+    fn signatures(entries: &[(AccountId32, &str)]) -> Value {
+        Value::unnamed_composite(entries.iter().map(|(account, status)| {
+            Value::unnamed_composite([
+                Value::unnamed_composite([Value::from_bytes(account.0)]),
+                Value::named_composite([(
+                    "signature",
+                    Value::variant(*status, Composite::Unnamed(vec![])),
+                )]),
+            ])
+        }))
+    }
+
+    #[test]
+    fn signers_are_the_leading_script_arguments() {
+        let alice = AccountAddress::from_hex_literal("0xa").unwrap();
+        let bob = AccountAddress::from_hex_literal("0xb").unwrap();
+        let script_tx = script_transaction(&[alice, bob]);
+
+        assert_eq!(
+            required_signers(&script_tx).unwrap(),
+            vec![
+                AccountId32::from(alice.into_bytes()),
+                AccountId32::from(bob.into_bytes())
+            ]
+        );
+        assert!(required_signers(&script_transaction(&[]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_script_transaction() {
+        assert!(required_signers(b"not a script transaction").is_err());
+    }
+
+    #[test]
+    fn signature_status_by_account() {
+        let (alice, bob, carol) = (
+            AccountId32([1; 32]),
+            AccountId32([2; 32]),
+            AccountId32([3; 32]),
+        );
+        let signatures = signatures(&[(alice.clone(), APPROVED), (bob.clone(), "Pending")]);
+
+        assert_eq!(
+            signature_status(&signatures, &alice).as_deref(),
+            Some(APPROVED)
+        );
+        assert_eq!(
+            signature_status(&signatures, &bob).as_deref(),
+            Some("Pending")
+        );
+        assert_eq!(signature_status(&signatures, &carol), None);
+        assert_eq!(signature_status(&Value::u128(1), &alice), None);
+    }
+
+    #[test]
+    fn account_bytes() {
+        let account = Value::unnamed_composite([Value::from_bytes([1, 2, 255])]);
+        assert_eq!(bytes(&account), Some(vec![1, 2, 255]));
+
+        let named = Value::named_composite([("id", Value::from_bytes([3]))]);
+        assert_eq!(bytes(&named), Some(vec![3]));
+    }
+
+    #[test]
+    fn account_bytes_reject_other_values() {
+        // 256 would wrap into 0 if cast into a byte.
+        let overflowing = Value::unnamed_composite([Value::u128(0), Value::u128(256)]);
+        assert_eq!(bytes(&overflowing), None);
+        assert_eq!(bytes(&Value::bool(true)), None);
+        assert_eq!(bytes(&Value::string("account")), None);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::cmd::node::{client::Connection, rpc::TokenAmount};
+use anyhow::{Error, Result};
+use clap::Parser;
+use jsonrpsee::rpc_params;
+use std::str::FromStr;
+use subxt::dynamic::Value;
+use subxt::ext::scale_value::At;
+use subxt::utils::{AccountId32, H256};
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// Query an account's balance.
+#[derive(Parser, Debug)]
+#[clap(about = "Show the account's free balance")]
+pub struct Balance {
+    #[clap(
+        short,
+        long,
+        help = "Account ID in the SS58 format (by default the network's account ID)"
+    )]
+    account_id: Option<String>,
+}
+
+impl Balance {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        let account_id = conn.account_id(self.account_id.as_ref())?;
+        let account = AccountId32::from_str(&account_id)
+            .map_err(|e| Error::msg(format!("Invalid account ID '{account_id}': {e:?}")))?;
+
+        let (free, properties) = conn.block_on(async {
+            let api = conn.subxt_client().await?;
+
+            let free = free_balance(&api, account, None).await?;
+
+            let client = conn.client().await?;
+            let properties: serde_json::Value =
+                client.request("system_properties", rpc_params![]).await?;
+
+            Ok::<_, Error>((free, properties))
+        })?;
+
+        println!(
+            "Free balance of {account_id}: {}",
+            TokenAmount::new(free, &properties)
+        );
+
+        Ok(())
+    }
+}
+
+/// Reads the account's free balance at the given block, or at the latest block.
+pub(crate) async fn free_balance(
+    api: &OnlineClient<PolkadotConfig>,
+    account: AccountId32,
+    at: Option<H256>,
+) -> Result<u128> {
+    let query = subxt::dynamic::storage("System", "Account", vec![Value::from_bytes(account)]);
+    let storage = match at {
+        Some(hash) => api.storage().at(hash),
+        None => api.storage().at_latest().await?,
+    };
+    let info = storage.fetch_or_default(&query).await?.to_value()?;
+
+    info.at("data")
+        .at("free")
+        .and_then(|free| free.as_u128())
+        .ok_or(Error::msg(
+            "Unexpected account data layout returned by the node",
+        ))
+}
@@ -0,0 +1,530 @@
+//! JSON-RPC client for accessing the node.
+
+use crate::config::NetworkProfile;
+use anyhow::{Context, Error, Result};
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::params::{ArrayParams, BatchRequestBuilder};
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::core::ClientError;
+use jsonrpsee::rpc_params;
+use jsonrpsee::types::error::METHOD_NOT_FOUND_CODE;
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use subxt::backend::rpc::RpcClient as SubxtRpcClient;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio::runtime::Runtime;
+use url::Url;
+
+/// Default number of retries for requests failing on the transport level.
+const DEFAULT_RETRIES: u32 = 2;
+
+/// Delay before the first retry, doubled for every following retry.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Upper limit of the delay between retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Resolved settings for accessing the node.
+///
+/// The connection owns the runtime shared by all requests of a command.
+pub(crate) struct Connection {
+    /// Node's URL.
+    pub(crate) url: Url,
+    /// Request timeout.
+    timeout: Option<Duration>,
+    /// Number of retries for requests failing on the transport level.
+    retries: u32,
+    /// Additional HTTP headers sent with every request.
+    headers: HeaderMap,
+    /// Custom CA bundle in the PEM format for verifying the node's certificate.
+    ca_bundle: Option<PathBuf>,
+    /// HTTP proxy.
+    proxy: Option<Url>,
+    /// Default account ID in the SS58 format.
+    account_id: Option<String>,
+    /// Runtime for running the requests.
+    runtime: Runtime,
+}
+
+impl Connection {
+    /// Create a new instance.
+    pub(crate) fn new(url: Url, profile: &NetworkProfile) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in profile.headers.iter() {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name '{name}'"))?;
+            let value = HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for the header '{name}'"))?;
+            headers.insert(name, value);
+        }
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        let conn = Self {
+            url,
+            timeout: profile.timeout.map(Duration::from_secs),
+            retries: profile.retries.unwrap_or(DEFAULT_RETRIES),
+            headers,
+            ca_bundle: profile.ca_bundle.clone(),
+            proxy: profile.proxy.clone(),
+            account_id: profile.account_id.clone(),
+            runtime,
+        };
+
+        // Every request to a WebSocket URL goes over WebSocket, so fail before any work is done.
+        if matches!(conn.url.scheme(), "ws" | "wss") {
+            conn.check_websocket()?;
+        }
+
+        Ok(conn)
+    }
+
+    /// Checks the settings can be used for the WebSocket transport.
+    ///
+    /// The commands requiring subscriptions (e.g. `submit` and `fork`) always connect over
+    /// WebSocket, which doesn't support the CA bundle and the proxy, so they call it before doing
+    /// anything.
+    pub(crate) fn check_websocket(&self) -> Result<()> {
+        if self.ca_bundle.is_some() || self.proxy.is_some() {
+            return Err(Error::msg(
+                "The CA bundle and the proxy (from the options or the network profile) are supported only over http(s)://, \
+                but this command connects to the node over WebSocket: unset them, or use a node reachable without them",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Runs the future to completion on the connection's runtime.
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Returns the given account ID or the default one from the network profile.
+    pub(crate) fn account_id(&self, account_id: Option<&String>) -> Result<String> {
+        account_id
+            .or(self.account_id.as_ref())
+            .cloned()
+            .ok_or(Error::msg(
+                "Account ID is required: use --account-id or set `account_id` for the network",
+            ))
+    }
+
+    /// Connects to the node using the transport chosen by the URL scheme.
+    pub(crate) async fn client(&self) -> Result<RpcClient> {
+        let transport = match self.url.scheme() {
+            "http" | "https" => Transport::Http(self.http_client()?),
+            "ws" | "wss" => Transport::Ws(self.ws_client(&self.url).await?),
+            scheme => return Err(Error::msg(format!("Unsupported URL scheme '{scheme}'"))),
+        };
+
+        Ok(RpcClient {
+            transport,
+            retries: self.retries,
+        })
+    }
+
+    /// Connects to the node with a client supporting subscriptions.
+    ///
+    /// HTTP URLs are switched to the WebSocket scheme since subscriptions require WebSocket.
+    pub(crate) async fn subxt_client(&self) -> Result<OnlineClient<PolkadotConfig>> {
+        let mut url = self.url.clone();
+        let scheme = match url.scheme() {
+            "http" | "ws" => "ws",
+            "https" | "wss" => "wss",
+            scheme => return Err(Error::msg(format!("Unsupported URL scheme '{scheme}'"))),
+        };
+
+        url.set_scheme(scheme)
+            .map_err(|_| Error::msg(format!("Can't use the URL {url} for WebSocket")))?;
+
+        let client = self.ws_client(&url).await?;
+
+        OnlineClient::<PolkadotConfig>::from_rpc_client(SubxtRpcClient::new(client))
+            .await
+            .with_context(|| format!("Can't connect to {url}"))
+    }
+
+    /// Creates the client for the HTTP transport.
+    fn http_client(&self) -> Result<HttpClient> {
+        let mut builder = reqwest::Client::builder().default_headers(self.headers.clone());
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(ref ca_bundle) = self.ca_bundle {
+            let pem = std::fs::read(ca_bundle)
+                .with_context(|| format!("Failure to read filename {}", ca_bundle.display()))?;
+            let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA bundle {}", ca_bundle.display()))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        if let Some(ref proxy) = self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+        }
+
+        Ok(HttpClient {
+            client: builder.build()?,
+            url: self.url.clone(),
+        })
+    }
+
+    /// Connects to the node using the WebSocket transport.
+    async fn ws_client(&self, url: &Url) -> Result<WsClient> {
+        self.check_websocket()?;
+
+        let mut builder = WsClientBuilder::default().set_headers(self.headers.clone());
+        if let Some(timeout) = self.timeout {
+            builder = builder.request_timeout(timeout);
+        }
+
+        builder
+            .build(url)
+            .await
+            .with_context(|| format!("Can't connect to {url}"))
+    }
+}
+
+/// Transport chosen by the URL scheme.
+enum Transport {
+    /// HTTP transport for `http://` and `https://` URLs.
+    Http(HttpClient),
+    /// WebSocket transport for `ws://` and `wss://` URLs.
+    Ws(WsClient),
+}
+
+/// RPC client retrying the requests which failed on the transport level.
+pub(crate) struct RpcClient {
+    /// Transport for sending the requests.
+    transport: Transport,
+    /// Number of retries.
+    retries: u32,
+}
+
+impl RpcClient {
+    /// Sends a request and deserializes the response.
+    pub(crate) async fn request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: ArrayParams,
+    ) -> Result<R> {
+        match self.request_with_retries(method, params).await {
+            Ok(response) => Ok(response),
+            // Give an actionable error in case the node doesn't provide the method at all.
+            Err(RequestError::MethodNotFound(e)) => Err(e.context(format!(
+                "The node doesn't provide the `{method}` RPC method, check the node's capabilities with `smove node info`"
+            ))),
+            Err(e) => Err(Error::from(e).context("RPC result failure")),
+        }
+    }
+
+    /// Returns the list of RPC methods provided by the node.
+    pub(crate) async fn methods(&self) -> Result<Vec<String>> {
+        /// Response of the `rpc_methods` RPC call.
+        #[derive(Deserialize)]
+        struct RpcMethods {
+            methods: Vec<String>,
+        }
+
+        let response: RpcMethods = self
+            .request_with_retries("rpc_methods", rpc_params![])
+            .await
+            .map_err(Error::from)
+            .with_context(|| "RPC result failure")?;
+
+        Ok(response.methods)
+    }
+
+    /// Sends the requests in a single batch and returns the responses in the same order.
+    pub(crate) async fn batch_request<R: DeserializeOwned + fmt::Debug>(
+        &self,
+        requests: Vec<(&str, ArrayParams)>,
+    ) -> Result<Vec<Result<R>>> {
+        let mut attempt = 0;
+        loop {
+            let response = match self.transport {
+                Transport::Http(ref client) => client.batch_request(&requests).await,
+                Transport::Ws(ref client) => ws_batch_request(client, &requests).await,
+            };
+
+            match response {
+                Err(RequestError::Transport(e)) if attempt < self.retries => {
+                    attempt += 1;
+                    backoff(attempt, &e).await;
+                }
+                response => {
+                    return response
+                        .map_err(Error::from)
+                        .with_context(|| "RPC batch result failure")
+                }
+            }
+        }
+    }
+
+    /// Sends a request, retrying it with a growing delay on transport failures.
+    async fn request_with_retries<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: ArrayParams,
+    ) -> Result<R, RequestError> {
+        let mut attempt = 0;
+        loop {
+            let response = match self.transport {
+                Transport::Http(ref client) => client.request(method, params.clone()).await,
+                Transport::Ws(ref client) => client
+                    .request(method, params.clone())
+                    .await
+                    .map_err(RequestError::from),
+            };
+
+            match response {
+                Err(RequestError::Transport(e)) if attempt < self.retries => {
+                    attempt += 1;
+                    backoff(attempt, &e).await;
+                }
+                response => return response,
+            }
+        }
+    }
+}
+
+/// Sends the requests in a single batch over the WebSocket transport.
+async fn ws_batch_request<R: DeserializeOwned + fmt::Debug>(
+    client: &WsClient,
+    requests: &[(&str, ArrayParams)],
+) -> Result<Vec<Result<R>>, RequestError> {
+    let mut batch = BatchRequestBuilder::new();
+    for (method, params) in requests {
+        batch
+            .insert(method, params.clone())
+            .map_err(|e| RequestError::Response(e.into()))?;
+    }
+
+    Ok(client
+        .batch_request(batch)
+        .await?
+        .into_iter()
+        .map(|result| result.map_err(|e| Error::msg(e.to_string())))
+        .collect())
+}
+
+/// Waits before the retry, the delay doubles with every attempt up to [`MAX_BACKOFF`].
+async fn backoff(attempt: u32, error: &Error) {
+    let delay = backoff_delay(attempt);
+    eprintln!("Request failed: {error:#}, retrying in {delay:?}");
+    tokio::time::sleep(delay).await;
+}
+
+/// Returns the delay before the retry, starting from 1 for the first retry.
+fn backoff_delay(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Failure of a single request or a batch.
+enum RequestError {
+    /// The request didn't reach the node or the response got lost, so it's safe to retry.
+    Transport(Error),
+    /// The node doesn't provide the requested method.
+    MethodNotFound(Error),
+    /// The node responded with an error or an invalid response.
+    Response(Error),
+}
+
+impl From<ClientError> for RequestError {
+    fn from(e: ClientError) -> Self {
+        match e {
+            ClientError::Transport(_)
+            | ClientError::RestartNeeded(_)
+            | ClientError::RequestTimeout => Self::Transport(e.into()),
+            ClientError::Call(ref error) if error.code() == METHOD_NOT_FOUND_CODE => {
+                Self::MethodNotFound(e.into())
+            }
+            e => Self::Response(e.into()),
+        }
+    }
+}
+
+impl From<RequestError> for Error {
+    fn from(e: RequestError) -> Self {
+        match e {
+            RequestError::Transport(e)
+            | RequestError::MethodNotFound(e)
+            | RequestError::Response(e) => e,
+        }
+    }
+}
+
+/// JSON-RPC client over HTTP.
+///
+/// Unlike the jsonrpsee HTTP client, it supports custom CA bundles and proxies.
+struct HttpClient {
+    /// HTTP client.
+    client: reqwest::Client,
+    /// Node's URL.
+    url: Url,
+}
+
+impl HttpClient {
+    /// Sends a single request.
+    async fn request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: ArrayParams,
+    ) -> Result<R, RequestError> {
+        let body = request_body(0, method, params).map_err(RequestError::Response)?;
+        let response: Response = self.post(&body).await?;
+
+        response.into_result()
+    }
+
+    /// Sends the requests in a single batch.
+    async fn batch_request<R: DeserializeOwned>(
+        &self,
+        requests: &[(&str, ArrayParams)],
+    ) -> Result<Vec<Result<R>>, RequestError> {
+        let body = requests
+            .iter()
+            .enumerate()
+            .map(|(id, (method, params))| request_body(id as u64, method, params.clone()))
+            .collect::<Result<Vec<_>>>()
+            .map_err(RequestError::Response)?;
+
+        let mut responses: Vec<Response> = self.post(&body).await?;
+        if responses.len() != requests.len() {
+            return Err(RequestError::Response(Error::msg(format!(
+                "Expected {} responses in the batch, got {}",
+                requests.len(),
+                responses.len()
+            ))));
+        }
+
+        // The node may respond in any order.
+        responses.sort_by_key(|response| response.id);
+
+        Ok(responses
+            .into_iter()
+            .map(|response| response.into_result().map_err(Error::from))
+            .collect())
+    }
+
+    /// Posts the JSON body and deserializes the response.
+    async fn post<B: serde::Serialize, R: DeserializeOwned>(
+        &self,
+        body: &B,
+    ) -> Result<R, RequestError> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| RequestError::Transport(e.into()))?;
+
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(RequestError::Transport(Error::msg(format!(
+                "HTTP status {status}"
+            ))));
+        } else if !status.is_success() {
+            return Err(RequestError::Response(Error::msg(format!(
+                "HTTP status {status}"
+            ))));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| RequestError::Response(e.into()))
+    }
+}
+
+/// JSON-RPC response.
+#[derive(Deserialize)]
+struct Response {
+    /// Request ID, null in case the node couldn't read it from the request.
+    id: Option<u64>,
+    /// Result of a successful call.
+    result: Option<serde_json::Value>,
+    /// Error of a failed call.
+    error: Option<ResponseError>,
+}
+
+impl Response {
+    /// Deserializes the result or returns the node's error.
+    fn into_result<R: DeserializeOwned>(self) -> Result<R, RequestError> {
+        if let Some(error) = self.error {
+            let message = Error::msg(error.to_string());
+            return Err(if error.code == i64::from(METHOD_NOT_FOUND_CODE) {
+                RequestError::MethodNotFound(message)
+            } else {
+                RequestError::Response(message)
+            });
+        }
+
+        serde_json::from_value(self.result.unwrap_or_default())
+            .map_err(|e| RequestError::Response(e.into()))
+    }
+}
+
+/// JSON-RPC error object.
+#[derive(Deserialize)]
+struct ResponseError {
+    /// Error code.
+    code: i64,
+    /// Error message.
+    message: String,
+    /// Additional error details.
+    data: Option<serde_json::Value>,
+}
+
+impl fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RPC error {}: {}", self.code, self.message)?;
+        if let Some(ref data) = self.data {
+            write!(f, " ({data})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the JSON-RPC request object.
+fn request_body(id: u64, method: &str, params: ArrayParams) -> Result<serde_json::Value> {
+    let params = match params.to_rpc_params()? {
+        Some(params) => serde_json::from_str(params.get())?,
+        None => serde_json::Value::Array(vec![]),
+    };
+
+    Ok(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        assert_eq!(backoff_delay(1), INITIAL_BACKOFF);
+        assert_eq!(backoff_delay(2), INITIAL_BACKOFF * 2);
+        assert_eq!(backoff_delay(3), INITIAL_BACKOFF * 4);
+        assert_eq!(backoff_delay(10), MAX_BACKOFF);
+        assert_eq!(backoff_delay(u32::MAX), MAX_BACKOFF);
+    }
+}
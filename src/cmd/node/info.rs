@@ -0,0 +1,108 @@
+use crate::cmd::node::client::{Connection, RpcClient};
+use crate::run_context::RunContext;
+use anyhow::Result;
+use jsonrpsee::rpc_params;
+use move_binary_format::file_format::empty_module;
+use move_binary_format::file_format_common::VERSION_DEFAULT;
+use move_core_types::vm_status::StatusCode;
+use serde::Deserialize;
+
+/// RPC methods provided by pallet-move.
+pub(crate) const PALLET_MOVE_RPC_METHODS: &[&str] = &[
+    "mvm_estimateGasPublishModule",
+    "mvm_estimateGasPublishBundle",
+    "mvm_estimateGasExecuteScript",
+    "mvm_getModuleABI",
+    "mvm_getModule",
+    "mvm_getResource",
+];
+
+/// Account used for probing the node, the module is never published.
+const PROBE_ACCOUNT_ID: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+/// Runtime version returned by the `state_getRuntimeVersion` RPC call.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeVersion {
+    spec_name: String,
+    spec_version: u32,
+    impl_version: u32,
+    transaction_version: u32,
+}
+
+/// Partial gas estimation response, only the status code is needed.
+#[derive(Deserialize)]
+struct ProbeEstimation {
+    vm_status_code: StatusCode,
+}
+
+/// Shows the node's version and capabilities.
+pub fn execute(conn: &Connection, ctx: &RunContext) -> Result<()> {
+    conn.block_on(async {
+        let client = conn.client().await?;
+
+        let chain: String = client.request("system_chain", rpc_params![]).await?;
+        let version: String = client.request("system_version", rpc_params![]).await?;
+        let runtime: RuntimeVersion = client
+            .request("state_getRuntimeVersion", rpc_params![])
+            .await?;
+
+        println!("Node: {}", conn.url);
+        println!("Chain: {chain}");
+        println!("Node version: {version}");
+        println!(
+            "Runtime: {} (spec version: {}, impl version: {}, transaction version: {})",
+            runtime.spec_name,
+            runtime.spec_version,
+            runtime.impl_version,
+            runtime.transaction_version
+        );
+
+        let methods = client.methods().await?;
+        println!("pallet-move RPC methods:");
+        for method in PALLET_MOVE_RPC_METHODS {
+            let available = methods.iter().any(|m| m == method);
+            println!(
+                "    {method}: {}",
+                if available { "available" } else { "missing" }
+            );
+        }
+
+        if !methods.iter().any(|m| m == "mvm_estimateGasPublishModule") {
+            println!("Warning: can't check the supported bytecode version without the gas estimation");
+            return Ok(());
+        }
+
+        let bytecode_version = ctx
+            .move_args
+            .build_config
+            .compiler_config
+            .bytecode_version
+            .unwrap_or(VERSION_DEFAULT);
+        if is_bytecode_version_supported(&client, bytecode_version).await? {
+            println!("Bytecode version {bytecode_version}: supported");
+        } else {
+            println!(
+                "Warning: the node doesn't support the bytecode version {bytecode_version}, compile with a lower `--bytecode-version`"
+            );
+        }
+
+        Ok(())
+    })
+}
+
+/// Checks if the node's MoveVM supports the bytecode version.
+///
+/// An empty module serialized with the given version is sent for the gas estimation, since the
+/// deserialization fails with the `UNKNOWN_VERSION` status code for unsupported versions.
+async fn is_bytecode_version_supported(client: &RpcClient, version: u32) -> Result<bool> {
+    let mut module_bytes = vec![];
+    empty_module().serialize_for_version(Some(version), &mut module_bytes)?;
+
+    let params = rpc_params![PROBE_ACCOUNT_ID, module_bytes];
+    let estimation: ProbeEstimation = client
+        .request("mvm_estimateGasPublishModule", params)
+        .await?;
+
+    Ok(estimation.vm_status_code != StatusCode::UNKNOWN_VERSION)
+}
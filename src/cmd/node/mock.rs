@@ -0,0 +1,250 @@
+//! Mock pallet-move JSON-RPC server backed by the embedded MoveVM.
+//!
+//! The storage is seeded with the package's dependencies and bundles (or its modules, if there
+//! are no bundles). Gas estimations never change the storage, as on the node.
+//!
+//! Without the runtime's benchmarks, the weights and fees are derived from the gas with the
+//! mock's own fixed rates.
+
+use crate::cmd::node::info::PALLET_MOVE_RPC_METHODS;
+use crate::cmd::node::rpc::{Estimation, Weight, QUERY_WEIGHT_TO_FEE};
+use crate::cmd::script_args::args::{parse_address, HexEncodedBytes};
+use crate::local_vm::{package_storage, Execution, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::run_context::RunContext;
+use anyhow::Result;
+use clap::Parser;
+use jsonrpsee::server::{RpcModule, Server};
+use jsonrpsee::types::error::{ErrorObjectOwned, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE};
+use jsonrpsee::types::Params;
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::resolver::{ModuleResolver, ResourceResolver};
+use move_vm_backend_common::abi::ModuleAbi;
+use move_vm_backend_common::types::ScriptTransaction;
+use move_vm_test_utils::InMemoryStorage;
+use parity_scale_codec::{Decode, Encode};
+use serde_json::json;
+use std::fmt::{Debug, Display};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::str::FromStr;
+
+/// Chain name reported by the mock server.
+const MOCK_CHAIN: &str = "smove mock";
+
+/// Weight of an extrinsic without the gas used.
+const MOCK_BASE_REF_TIME: u64 = 100_000_000;
+
+/// Weight charged for each unit of gas used.
+const MOCK_REF_TIME_PER_GAS: u64 = 1_000;
+
+/// Token decimals reported by the mock server, the fee is one smallest unit per `ref_time`.
+const MOCK_TOKEN_DECIMALS: u32 = 12;
+
+/// Token symbol reported by the mock server.
+const MOCK_TOKEN_SYMBOL: &str = "UNIT";
+
+/// Serve the pallet-move RPC methods without running a chain.
+#[derive(Parser, Debug)]
+pub struct Mock {
+    #[clap(
+        long,
+        default_value = "9944",
+        help = "Port to listen on, 0 for any free port"
+    )]
+    port: u16,
+
+    #[clap(long, help = "Address to listen on (by default 127.0.0.1)")]
+    host: Option<IpAddr>,
+}
+
+impl Mock {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let vm = LocalVm::new(ctx)?;
+        let storage = package_storage(ctx, &vm)?;
+        let module = rpc_module(MockState { vm, storage })?;
+
+        let host = self.host.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let server = Server::builder()
+                .build(SocketAddr::new(host, self.port))
+                .await?;
+            let address = server.local_addr()?;
+            let handle = server.start(module);
+
+            println!("Mock pallet-move RPC server is listening on http://{address}/");
+            println!(
+                "Serving the methods: {}",
+                PALLET_MOVE_RPC_METHODS.join(", ")
+            );
+
+            handle.stopped().await;
+
+            Ok(())
+        })
+    }
+}
+
+/// State shared by the RPC methods.
+struct MockState {
+    /// Virtual machine.
+    vm: LocalVm,
+    /// Published modules and resources.
+    storage: InMemoryStorage,
+}
+
+/// Registers the pallet-move RPC methods and the methods needed by `smove node info`.
+fn rpc_module(state: MockState) -> Result<RpcModule<MockState>> {
+    let mut module = RpcModule::new(state);
+
+    module.register_method("mvm_estimateGasPublishModule", |params, state| {
+        let mut params = params.sequence();
+        let sender = parse_address(&params.next::<String>()?).map_err(invalid_params)?;
+        let bytecode: Vec<u8> = params.next()?;
+
+        state
+            .vm
+            .publish_module(&state.storage, bytecode, sender, ESTIMATION_GAS_LIMIT)
+            .map(estimation)
+            .map_err(internal_error)
+    })?;
+
+    module.register_method("mvm_estimateGasPublishBundle", |params, state| {
+        let mut params = params.sequence();
+        let sender = parse_address(&params.next::<String>()?).map_err(invalid_params)?;
+        let bundle: Vec<u8> = params.next()?;
+
+        state
+            .vm
+            .publish_bundle(&state.storage, &bundle, sender, ESTIMATION_GAS_LIMIT)
+            .map(estimation)
+            .map_err(internal_error)
+    })?;
+
+    module.register_method("mvm_estimateGasExecuteScript", |params, state| {
+        let script_tx: Vec<u8> = params.sequence().next()?;
+        let tx: ScriptTransaction = bcs::from_bytes(&script_tx).map_err(invalid_params)?;
+
+        state
+            .vm
+            .execute_script(&state.storage, &tx, ESTIMATION_GAS_LIMIT)
+            .map(estimation)
+            .map_err(internal_error)
+    })?;
+
+    module.register_method("mvm_getModule", |params, state| {
+        let id = module_id(params)?;
+
+        state.storage.get_module(&id).map_err(storage_error)
+    })?;
+
+    module.register_method("mvm_getModuleABI", |params, state| {
+        let id = module_id(params)?;
+
+        let Some(bytecode) = state.storage.get_module(&id).map_err(storage_error)? else {
+            return Ok(None);
+        };
+        let module = CompiledModule::deserialize(&bytecode).map_err(storage_error)?;
+
+        Ok::<_, ErrorObjectOwned>(Some(ModuleAbi::from(module)))
+    })?;
+
+    module.register_method("mvm_getResource", |params, state| {
+        let mut params = params.sequence();
+        let address = parse_address(&params.next::<String>()?).map_err(invalid_params)?;
+        let tag: Vec<u8> = params.next()?;
+        let tag: StructTag = bcs::from_bytes(&tag).map_err(invalid_params)?;
+
+        state
+            .storage
+            .get_resource(&address, &tag)
+            .map_err(storage_error)
+    })?;
+
+    module.register_method("system_chain", |_, _| {
+        Ok::<_, ErrorObjectOwned>(MOCK_CHAIN.to_string())
+    })?;
+    module.register_method("system_version", |_, _| {
+        Ok::<_, ErrorObjectOwned>(env!("CARGO_PKG_VERSION").to_string())
+    })?;
+    module.register_method("system_properties", |_, _| {
+        Ok::<_, ErrorObjectOwned>(json!({
+            "tokenDecimals": MOCK_TOKEN_DECIMALS,
+            "tokenSymbol": MOCK_TOKEN_SYMBOL,
+        }))
+    })?;
+    module.register_method("state_call", |params, _| {
+        let mut params = params.sequence();
+        let method: String = params.next()?;
+        let data: String = params.next()?;
+        if method != QUERY_WEIGHT_TO_FEE {
+            return Err(invalid_params(format!(
+                "The runtime API method {method} isn't supported by the mock"
+            )));
+        }
+
+        let data = HexEncodedBytes::from_str(&data).map_err(invalid_params)?;
+        let weight = Weight::decode(&mut &data.into_inner()[..]).map_err(invalid_params)?;
+
+        Ok(HexEncodedBytes::from(u128::from(weight.ref_time).encode()).to_string())
+    })?;
+    module.register_method("state_getRuntimeVersion", |_, _| {
+        Ok::<_, ErrorObjectOwned>(json!({
+            "specName": MOCK_CHAIN,
+            "specVersion": 0,
+            "implVersion": 0,
+            "transactionVersion": 0,
+        }))
+    })?;
+
+    let mut methods: Vec<String> = module.method_names().map(String::from).collect();
+    methods.push("rpc_methods".to_string());
+    module.register_method("rpc_methods", move |_, _| {
+        Ok::<_, ErrorObjectOwned>(json!({ "methods": methods }))
+    })?;
+
+    Ok(module)
+}
+
+/// Converts the execution into the estimation, with the weight derived from the gas used.
+fn estimation(execution: Execution) -> Estimation {
+    let mut estimation = execution.estimation();
+    estimation.total_weight_including_gas_used = Some(Weight {
+        ref_time: MOCK_BASE_REF_TIME
+            .saturating_add(estimation.gas_used.saturating_mul(MOCK_REF_TIME_PER_GAS)),
+        proof_size: 0,
+    });
+
+    estimation
+}
+
+/// Parses the module's address and name parameters.
+fn module_id(params: Params) -> Result<ModuleId, ErrorObjectOwned> {
+    let mut params = params.sequence();
+    let address: AccountAddress =
+        parse_address(&params.next::<String>()?).map_err(invalid_params)?;
+    let name = Identifier::new(params.next::<String>()?).map_err(invalid_params)?;
+
+    Ok(ModuleId::new(address, name))
+}
+
+/// Creates the error for invalid parameters.
+fn invalid_params(e: impl Display) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INVALID_PARAMS_CODE, e.to_string(), None::<()>)
+}
+
+/// Creates the error for failures while handling the request.
+fn internal_error(e: impl Display) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, e.to_string(), None::<()>)
+}
+
+/// Creates the error for storage failures, which may have no displayable error type.
+fn storage_error(e: impl Debug) -> ErrorObjectOwned {
+    internal_error(format!("{e:?}"))
+}
@@ -1,8 +1,18 @@
-use anyhow::Result;
+use crate::config::NetworkProfile;
+use crate::run_context::RunContext;
+use anyhow::{Context, Result};
 use clap::Parser;
+use client::Connection;
+use std::path::PathBuf;
 use url::Url;
 
+pub(crate) mod balance;
+pub(crate) mod client;
+pub(crate) mod info;
+pub(crate) mod mock;
 pub(crate) mod rpc;
+pub(crate) mod submit;
+pub(crate) mod watch_events;
 
 /// Commands for accessing the node.
 #[derive(Parser)]
@@ -11,29 +21,187 @@ pub struct Node {
     #[clap(subcommand)]
     cmd: NodeCmd,
 
+    /// Node access options.
+    #[clap(flatten)]
+    node_args: NodeArgs,
+}
+
+/// Environment variable for choosing the network profile.
+const NETWORK_ENV: &str = "SMOVE_NETWORK";
+/// Environment variable overriding the node's URL.
+const URL_ENV: &str = "SMOVE_URL";
+/// Environment variable overriding the default account ID.
+const ACCOUNT_ID_ENV: &str = "SMOVE_ACCOUNT_ID";
+
+/// Default node's URL.
+const DEFAULT_URL: &str = "http://localhost:9944/";
+
+/// Node access options.
+#[derive(Parser, Debug)]
+pub struct NodeArgs {
     /// URL for the node's endpoint depending on the chosen option.
+    ///
+    /// The transport is chosen by the URL scheme: `http(s)://` or `ws(s)://`.
     #[clap(
         short,
         long,
-        help = "Node's URL (by default using local RPC's URL)",
-        default_value = "http://localhost:9944/"
+        global = true,
+        help = "Node's URL with the http(s):// or ws(s):// scheme (by default using local RPC's URL)"
+    )]
+    url: Option<Url>,
+
+    /// Network profile defined in the smove configuration.
+    #[clap(
+        long,
+        global = true,
+        help = "Name of the network profile defined in the smove.toml configuration"
+    )]
+    network: Option<String>,
+
+    #[clap(long, global = true, help = "Request timeout in seconds")]
+    timeout: Option<u64>,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Number of retries for requests failing on the transport level (by default 2)"
     )]
-    url: Url,
+    retries: Option<u32>,
+
+    /// Additional HTTP headers, e.g. `Authorization: Bearer <TOKEN>`.
+    #[clap(
+        long = "header",
+        global = true,
+        parse(try_from_str = parse_header),
+        help = "Additional HTTP header in the `NAME: VALUE` format, can be repeated"
+    )]
+    headers: Vec<(String, String)>,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Custom CA bundle in the PEM format for verifying the node's certificate, \
+        http(s):// only: the WebSocket commands (submit, balance, watch-events, multisig status and fork) refuse it"
+    )]
+    ca_bundle: Option<PathBuf>,
+
+    #[clap(
+        long,
+        global = true,
+        help = "HTTP proxy URL, http(s):// only: the WebSocket commands \
+        (submit, balance, watch-events, multisig status and fork) refuse it"
+    )]
+    proxy: Option<Url>,
+}
+
+/// Parses the header in the `NAME: VALUE` format.
+fn parse_header(s: &str) -> Result<(String, String)> {
+    let (name, value) = s
+        .split_once(':')
+        .with_context(|| format!("Invalid header '{s}', expected the `NAME: VALUE` format"))?;
+
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+impl NodeArgs {
+    /// Resolves the connection settings.
+    ///
+    /// Settings given on the command line take precedence over the environment variables, which
+    /// take precedence over the network profile.
+    pub(crate) fn connection(&self, ctx: &RunContext) -> Result<Connection> {
+        let network = self
+            .network
+            .clone()
+            .or_else(|| std::env::var(NETWORK_ENV).ok());
+
+        let mut profile = match network {
+            Some(ref name) => ctx.config()?.network(name)?.clone(),
+            None => NetworkProfile::default(),
+        };
+
+        if let Ok(url) = std::env::var(URL_ENV) {
+            profile.url = Some(Url::parse(&url)?);
+        }
+        if let Ok(account_id) = std::env::var(ACCOUNT_ID_ENV) {
+            profile.account_id = Some(account_id);
+        }
+        if let Some(ref url) = self.url {
+            profile.url = Some(url.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            profile.timeout = Some(timeout);
+        }
+        if let Some(retries) = self.retries {
+            profile.retries = Some(retries);
+        }
+        if let Some(ref ca_bundle) = self.ca_bundle {
+            profile.ca_bundle = Some(ca_bundle.clone());
+        }
+        if let Some(ref proxy) = self.proxy {
+            profile.proxy = Some(proxy.clone());
+        }
+        profile.headers.extend(self.headers.iter().cloned());
+
+        Connection::new(profile.url.unwrap_or(Url::parse(DEFAULT_URL)?), &profile)
+    }
 }
 
 /// List of possible node access commands.
 #[derive(Parser)]
 pub enum NodeCmd {
+    /// Show the node's version and capabilities.
+    #[clap(about = "Show the node's version and available pallet-move RPC methods")]
+    Info,
+
+    /// Show an account's balance.
+    #[clap(about = "Show the account's free balance")]
+    Balance {
+        #[clap(flatten)]
+        cmd: balance::Balance,
+    },
+
+    /// Serve a mock of the pallet-move RPC methods.
+    #[clap(
+        about = "Serve the pallet-move RPC methods backed by the embedded MoveVM and the package's builds"
+    )]
+    Mock {
+        #[clap(flatten)]
+        cmd: mock::Mock,
+    },
+
     /// Access node's RPC requests.
     #[clap(subcommand, about = "Access node's RPC requests")]
     Rpc(Rpc),
+
+    /// Sign and submit pallet-move extrinsics.
+    #[clap(subcommand, about = "Sign and submit pallet-move extrinsics")]
+    Submit(submit::Submit),
+
+    /// Watch pallet-move events.
+    #[clap(about = "Subscribe to new blocks and print pallet-move events")]
+    WatchEvents {
+        #[clap(flatten)]
+        cmd: watch_events::WatchEvents,
+    },
 }
 
 impl Node {
     /// Executes the command.
-    pub fn execute(&mut self) -> Result<()> {
+    pub fn execute(&mut self, ctx: &RunContext) -> Result<()> {
+        // The mock is the node itself, so it doesn't connect to one.
+        if let NodeCmd::Mock { cmd } = &self.cmd {
+            return cmd.execute(ctx);
+        }
+
+        let conn = self.node_args.connection(ctx)?;
+
         match &self.cmd {
-            NodeCmd::Rpc(rpc) => rpc.execute(&self.url),
+            NodeCmd::Info => info::execute(&conn, ctx),
+            NodeCmd::Balance { cmd } => cmd.execute(&conn),
+            NodeCmd::Mock { .. } => unreachable!("the mock is served without a connection"),
+            NodeCmd::Rpc(rpc) => rpc.execute(&conn, ctx),
+            NodeCmd::Submit(submit) => submit.execute(&conn),
+            NodeCmd::WatchEvents { cmd } => cmd.execute(&conn),
         }
     }
 }
@@ -61,22 +229,68 @@ pub enum Rpc {
         cmd: rpc::estimate_gas_execute::EstimateGasExecuteScript,
     },
 
+    /// Estimate gas for all script transactions and bundles.
+    #[clap(about = "Estimate gas for all script transactions and bundles in the package")]
+    EstimateAll {
+        #[clap(flatten)]
+        cmd: rpc::estimate_all::EstimateAll,
+    },
+
     /// Get a module's ABI.
     #[clap(about = "Get a module's ABI")]
     GetModuleAbi {
         #[clap(flatten)]
         cmd: rpc::get_module_abi::GetModuleAbi,
     },
+
+    /// Call any RPC method.
+    #[clap(about = "Call any RPC method with the given parameters")]
+    Call {
+        #[clap(flatten)]
+        cmd: rpc::call::Call,
+    },
 }
 
 impl Rpc {
     /// Executes the command.
-    pub fn execute(&self, url: &Url) -> Result<()> {
+    pub fn execute(&self, conn: &Connection, ctx: &RunContext) -> Result<()> {
         match self {
-            Self::EstimateGasPublishModule { cmd } => cmd.execute(url),
-            Self::EstimateGasPublishBundle { cmd } => cmd.execute(url),
-            Self::EstimateGasExecuteScript { cmd } => cmd.execute(url),
-            Self::GetModuleAbi { cmd } => cmd.execute(url),
+            Self::EstimateGasPublishModule { cmd } => cmd.execute(conn, ctx),
+            Self::EstimateGasPublishBundle { cmd } => cmd.execute(conn, ctx),
+            Self::EstimateGasExecuteScript { cmd } => cmd.execute(conn, ctx),
+            Self::EstimateAll { cmd } => cmd.execute(conn, ctx),
+            Self::GetModuleAbi { cmd } => cmd.execute(conn),
+            Self::Call { cmd } => cmd.execute(conn),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_is_trimmed() {
+        assert_eq!(
+            parse_header("Authorization: Bearer token").unwrap(),
+            ("Authorization".to_string(), "Bearer token".to_string())
+        );
+        assert_eq!(
+            parse_header(" X-Api-Key:key ").unwrap(),
+            ("X-Api-Key".to_string(), "key".to_string())
+        );
+    }
+
+    #[test]
+    fn header_value_may_contain_colons() {
+        assert_eq!(
+            parse_header("X-Forwarded-For: ::1").unwrap(),
+            ("X-Forwarded-For".to_string(), "::1".to_string())
+        );
+    }
+
+    #[test]
+    fn header_without_value() {
+        assert!(parse_header("Authorization").is_err());
+    }
+}
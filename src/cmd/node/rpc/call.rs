@@ -0,0 +1,157 @@
+use crate::cmd::node::client::Connection;
+use crate::cmd::read_bytes;
+use crate::cmd::script_args::args::FunctionArgType;
+use anyhow::{Error, Result};
+use clap::Parser;
+use jsonrpsee::core::params::ArrayParams;
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Call any RPC method provided by the node.
+#[derive(Parser, Debug)]
+#[clap(about = "Call any RPC method with the given parameters")]
+pub struct Call {
+    #[clap(help = "Name of the RPC method, e.g. mvm_getModuleABI")]
+    method: String,
+
+    /// Parameters combined with their type separated by a colon.
+    ///
+    /// Supported types are the script argument types [address, bool, hex, string, u8, u16, u32,
+    /// u64, u128, u256, raw], plus [file, json] for the file content as bytes and raw JSON.
+    ///
+    /// Example: `address:0x1 string:CarWash file:build/car-wash-example/bytecode_modules/CarWash.mv`
+    #[clap(
+        help = "Parameters in the <TYPE>:<VALUE> format, types: the script argument types (address, bool, hex, string, u8..u256, ...), file and json"
+    )]
+    params: Vec<RpcParam>,
+
+    #[clap(
+        long,
+        help = "Print the result as compact JSON instead of pretty-printed"
+    )]
+    raw: bool,
+}
+
+impl Call {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        let mut params = ArrayParams::new();
+        for param in self.params.iter() {
+            params.insert(&param.0)?;
+        }
+
+        let result: Value = conn.block_on(async {
+            let client = conn.client().await?;
+            client.request(&self.method, params).await
+        })?;
+
+        if self.raw {
+            println!("{result}");
+        } else {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+
+        Ok(())
+    }
+}
+
+/// RPC parameter converted into JSON.
+#[derive(Clone, Debug)]
+struct RpcParam(Value);
+
+impl FromStr for RpcParam {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on the first colon to support values that contain a colon.
+        let (ty, value) = s.split_once(':').ok_or(Error::msg(
+            "Parameters must be pairs of <type>:<value> e.g. string:CarWash",
+        ))?;
+
+        let value = match ty.to_lowercase().as_str() {
+            "file" => bytes_to_json(read_bytes(Path::new(value))?),
+            "json" => serde_json::from_str(value).map_err(Error::msg)?,
+            arg_type => FunctionArgType::from_str(arg_type)
+                .map_err(|e| Error::msg(format!("{e}, or one of: ['file','json']")))?
+                .parse_rpc_arg(value)?,
+        };
+
+        Ok(Self(value))
+    }
+}
+
+/// Converts bytes into a JSON array of numbers, as the node expects for `Vec<u8>` parameters.
+fn bytes_to_json(bytes: Vec<u8>) -> Value {
+    Value::Array(bytes.into_iter().map(Value::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn param(s: &str) -> Value {
+        RpcParam::from_str(s).unwrap().0
+    }
+
+    #[test]
+    fn addresses_are_converted_into_ss58() {
+        let ss58 = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+        assert_eq!(param(&format!("address:{ss58}")), json!(ss58));
+        assert_eq!(
+            param("address:0x1"),
+            json!("5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUqAsg")
+        );
+        assert_eq!(param("address:0x1"), param("signer:0x1"));
+    }
+
+    #[test]
+    fn script_argument_types() {
+        assert_eq!(param("bool:true"), json!(true));
+        assert_eq!(param("string:CarWash"), json!("CarWash"));
+        assert_eq!(param("u8:255"), json!(255));
+        assert_eq!(param("u64:18446744073709551615"), json!(u64::MAX));
+        assert_eq!(param("u128:42"), json!(42));
+        assert_eq!(
+            param("u128:340282366920938463463374607431768211455"),
+            json!("0xffffffffffffffffffffffffffffffff")
+        );
+        assert_eq!(
+            param("u128:18446744073709551616"),
+            json!("0x10000000000000000")
+        );
+        assert_eq!(
+            param("u256:[18446744073709551615, 18446744073709551616]"),
+            json!([u64::MAX, "0x10000000000000000"])
+        );
+        assert_eq!(
+            param("u128:[[340282366920938463463374607431768211455], []]"),
+            json!([["0xffffffffffffffffffffffffffffffff"], []])
+        );
+        assert_eq!(param("u256:7"), json!(7));
+        assert_eq!(param("hex:0x0102"), json!([1, 2]));
+        assert_eq!(param("u16:[1, 2]"), json!([1, 2]));
+        assert_eq!(
+            param("STRING:value:with:colons"),
+            json!("value:with:colons")
+        );
+    }
+
+    #[test]
+    fn rpc_specific_types() {
+        assert_eq!(param(r#"json:{"key": [1]}"#), json!({ "key": [1] }));
+        assert!(RpcParam::from_str("file:/nonexistent/module.mv").is_err());
+    }
+
+    #[test]
+    fn invalid_params() {
+        assert!(RpcParam::from_str("CarWash").is_err());
+        assert!(RpcParam::from_str("number:1").is_err());
+        assert!(RpcParam::from_str("u8:256").is_err());
+        assert!(RpcParam::from_str("bool:yes").is_err());
+        assert!(RpcParam::from_str("address:0xg").is_err());
+        assert!(RpcParam::from_str("json:{").is_err());
+    }
+}
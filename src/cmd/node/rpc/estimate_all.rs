@@ -0,0 +1,227 @@
+use crate::cmd::devnet::storage::DevnetState;
+use crate::cmd::estimate::offline_storage;
+use crate::cmd::node::{client::Connection, rpc::Estimation};
+use crate::cmd::read_bytes;
+use crate::cmd::script_args::args::parse_address;
+use crate::local_vm::{bundle_address, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use clap::Parser;
+use jsonrpsee::rpc_params;
+use move_vm_test_utils::InMemoryStorage;
+use std::path::{Path, PathBuf};
+
+/// Estimate gas for all script transactions and bundles in the package.
+#[derive(Parser, Debug)]
+#[clap(about = "Estimate gas for all script transactions and bundles in the package")]
+pub struct EstimateAll {
+    #[clap(
+        short,
+        long,
+        help = "Account ID in the SS58 format for publishing bundles (by default the network's account ID)"
+    )]
+    account_id: Option<String>,
+
+    #[clap(long, help = "Print the estimations in the JSON format")]
+    json: bool,
+}
+
+/// Gas estimation result for a single artifact.
+pub(crate) struct ArtifactEstimation {
+    /// Path to the artifact.
+    pub(crate) path: PathBuf,
+    /// Gas estimation or the reason why it failed.
+    pub(crate) estimation: Result<Estimation>,
+}
+
+impl EstimateAll {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection, ctx: &RunContext) -> Result<()> {
+        let results = estimate_all(conn, ctx, self.account_id.as_ref())?;
+
+        if self.json {
+            print_json(&results)
+        } else {
+            print_table(&results);
+            Ok(())
+        }
+    }
+}
+
+/// Estimates gas for all script transactions and bundles in the package using a single batch.
+pub(crate) fn estimate_all(
+    conn: &Connection,
+    ctx: &RunContext,
+    account_id: Option<&String>,
+) -> Result<Vec<ArtifactEstimation>> {
+    let (script_txs, bundles) = artifacts(ctx)?;
+
+    let mut requests = Vec::with_capacity(script_txs.len() + bundles.len());
+    for path in script_txs.iter() {
+        let script_tx = read_bytes(path)?;
+        requests.push(("mvm_estimateGasExecuteScript", rpc_params![script_tx]));
+    }
+    if !bundles.is_empty() {
+        let account_id = conn.account_id(account_id)?;
+        for path in bundles.iter() {
+            let bundle = read_bytes(path)?;
+            requests.push((
+                "mvm_estimateGasPublishBundle",
+                rpc_params![&account_id, bundle],
+            ));
+        }
+    }
+
+    let responses: Vec<Result<Estimation>> = conn.block_on(async {
+        let client = conn.client().await?;
+        client.batch_request(requests).await
+    })?;
+
+    Ok(script_txs
+        .into_iter()
+        .chain(bundles)
+        .zip(responses)
+        .map(|(path, estimation)| ArtifactEstimation { path, estimation })
+        .collect())
+}
+
+/// Estimates gas for all script transactions and bundles in the package with the embedded MoveVM.
+///
+/// The scripts are executed against the devnet state in the given directory, or against the
+/// package's builds. The bundles are published into the same state, or into the storage with only
+/// the package's dependencies, as they would be on a chain without the package.
+pub(crate) fn estimate_all_offline(
+    ctx: &RunContext,
+    account_id: Option<&String>,
+    state_dir: Option<&Path>,
+) -> Result<Vec<ArtifactEstimation>> {
+    let (script_txs, bundles) = artifacts(ctx)?;
+    let vm = LocalVm::new(ctx)?;
+
+    let storage = offline_storage(ctx, &vm, state_dir)?;
+    let script_estimations = script_txs.into_iter().map(|path| {
+        let estimation = read_bytes(&path).and_then(|script_tx| {
+            let tx = bcs::from_bytes(&script_tx)
+                .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+            Ok(vm
+                .execute_script(&storage, &tx, ESTIMATION_GAS_LIMIT)?
+                .estimation())
+        });
+        ArtifactEstimation { path, estimation }
+    });
+    let mut results = script_estimations.collect::<Vec<_>>();
+
+    if !bundles.is_empty() {
+        let storage = match state_dir {
+            Some(dir) => DevnetState::open(dir)?.storage,
+            None => {
+                let mut storage = InMemoryStorage::new();
+                LocalVm::publish_genesis_modules(&mut storage, &ctx.get_dependency_modules()?)?;
+                storage
+            }
+        };
+        let sender = account_id
+            .map(|account| parse_address(account))
+            .transpose()?;
+
+        results.extend(bundles.into_iter().map(|path| {
+            let estimation = read_bytes(&path).and_then(|bundle| {
+                let sender = match sender {
+                    Some(sender) => sender,
+                    None => bundle_address(&bundle)?,
+                };
+                let execution =
+                    vm.publish_bundle(&storage, &bundle, sender, ESTIMATION_GAS_LIMIT)?;
+                Ok(execution.estimation())
+            });
+            ArtifactEstimation { path, estimation }
+        }));
+    }
+
+    Ok(results)
+}
+
+/// Returns the paths of the package's script transactions and bundles, failing if there are
+/// none.
+fn artifacts(ctx: &RunContext) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let script_txs = ctx.get_script_transactions()?;
+    let bundles = ctx.get_bundles()?;
+
+    if script_txs.is_empty() && bundles.is_empty() {
+        return Err(Error::msg(
+            "No script transactions or bundles found, create them with `smove create-transaction` and `smove bundle`",
+        ));
+    }
+
+    Ok((script_txs, bundles))
+}
+
+/// Returns the artifact's file name.
+fn artifact_name(result: &ArtifactEstimation) -> String {
+    result
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Prints the estimations as a table.
+fn print_table(results: &[ArtifactEstimation]) {
+    let name_width = results
+        .iter()
+        .map(|result| artifact_name(result).len())
+        .chain(["ARTIFACT".len()])
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{:name_width$}  {:>12}  {:>16}  {:>12}  STATUS",
+        "ARTIFACT", "GAS", "REF_TIME", "PROOF_SIZE"
+    );
+
+    for result in results {
+        let name = artifact_name(result);
+        match result.estimation {
+            Ok(ref estimation) => {
+                let weight = estimation
+                    .total_weight_including_gas_used
+                    .unwrap_or_default();
+                println!(
+                    "{name:name_width$}  {:>12}  {:>16}  {:>12}  {:?}",
+                    estimation.gas_used,
+                    weight.ref_time,
+                    weight.proof_size,
+                    estimation.vm_status_code
+                )
+            }
+            Err(ref e) => println!(
+                "{name:name_width$}  {:>12}  {:>16}  {:>12}  RPC failure: {e}",
+                "-", "-", "-"
+            ),
+        }
+    }
+}
+
+/// Prints the estimations in the JSON format.
+fn print_json(results: &[ArtifactEstimation]) -> Result<()> {
+    let json = results
+        .iter()
+        .map(|result| match result.estimation {
+            Ok(ref estimation) => serde_json::json!({
+                "artifact": result.path,
+                "gas_used": estimation.gas_used,
+                "ref_time": estimation.total_weight_including_gas_used.map(|weight| weight.ref_time),
+                "proof_size": estimation.total_weight_including_gas_used.map(|weight| weight.proof_size),
+                "vm_status_code": format!("{:?}", estimation.vm_status_code),
+            }),
+            Err(ref e) => serde_json::json!({
+                "artifact": result.path,
+                "error": e.to_string(),
+            }),
+        })
+        .collect::<Vec<_>>();
+
+    println!("{}", serde_json::to_string_pretty(&json)?);
+
+    Ok(())
+}
@@ -1,11 +1,11 @@
-use crate::cmd::{node::rpc::Estimation, read_bytes};
-use anyhow::{Context, Result};
+use crate::cmd::node::client::Connection;
+use crate::cmd::node::rpc::{Estimation, EstimationArgs};
+use crate::cmd::read_bytes;
+use crate::run_context::RunContext;
+use anyhow::Result;
 use clap::Parser;
-use jsonrpsee::core::client::ClientT;
-use jsonrpsee::http_client::HttpClientBuilder;
 use jsonrpsee::rpc_params;
 use std::path::PathBuf;
-use url::Url;
 
 /// Estimate gas for publishing modules.
 #[derive(Parser, Debug)]
@@ -14,25 +14,26 @@ pub struct EstimateGasExecuteScript {
     /// Path to the script transaction (compiled by the smove create-transaction).
     #[clap(short, long)]
     script_transaction_path: PathBuf,
+
+    #[clap(flatten)]
+    estimation_args: EstimationArgs,
 }
 
 impl EstimateGasExecuteScript {
     /// Executes the command.
-    pub fn execute(&self, url: &Url) -> Result<()> {
+    pub fn execute(&self, conn: &Connection, ctx: &RunContext) -> Result<()> {
         let script_tx = read_bytes(&self.script_transaction_path)?;
 
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()?;
-
-        let client = HttpClientBuilder::default().build(url)?;
-        let params = rpc_params![script_tx];
-        let response: Result<Estimation, _> =
-            rt.block_on(async { client.request("mvm_estimateGasExecuteScript", params).await });
-
-        let estimated_gas = response.with_context(|| "RPC result failure")?;
+        let params = rpc_params![&script_tx];
+        let mut estimated_gas = Estimation::request(conn, "mvm_estimateGasExecuteScript", params)?;
+        estimated_gas.set_script_callees(ctx, &script_tx)?;
 
-        println!("{estimated_gas}");
+        self.estimation_args.print(
+            &estimated_gas,
+            &self.script_transaction_path,
+            Some(conn),
+            ctx,
+        )?;
 
         Ok(())
     }
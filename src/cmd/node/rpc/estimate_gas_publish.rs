@@ -1,40 +1,41 @@
-use crate::cmd::{node::rpc::Estimation, read_bytes};
-use anyhow::{Context, Result};
+use crate::cmd::node::client::Connection;
+use crate::cmd::node::rpc::{Estimation, EstimationArgs};
+use crate::cmd::read_bytes;
+use crate::run_context::RunContext;
+use anyhow::Result;
 use clap::Parser;
-use jsonrpsee::core::client::ClientT;
-use jsonrpsee::http_client::HttpClientBuilder;
 use jsonrpsee::rpc_params;
 use std::path::PathBuf;
-use url::Url;
 
 /// Estimate gas for publishing modules.
 #[derive(Parser, Debug)]
 #[clap(about = "Estimate gas for publishing modules")]
 pub struct EstimateGasPublishModule {
-    #[clap(short, long, help = "Account ID in the SS58 format")]
-    account_id: String,
+    #[clap(
+        short,
+        long,
+        help = "Account ID in the SS58 format (by default the network's account ID)"
+    )]
+    account_id: Option<String>,
 
     #[clap(short, long, help = "Path to the module (compiled by the smove)")]
     module_path: PathBuf,
+
+    #[clap(flatten)]
+    estimation_args: EstimationArgs,
 }
 
 impl EstimateGasPublishModule {
     /// Executes the command.
-    pub fn execute(&self, url: &Url) -> Result<()> {
+    pub fn execute(&self, conn: &Connection, ctx: &RunContext) -> Result<()> {
         let script_tx = read_bytes(&self.module_path)?;
 
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()?;
-
-        let client = HttpClientBuilder::default().build(url)?;
-        let params = rpc_params![&self.account_id, script_tx];
-        let response: Result<Estimation, _> =
-            rt.block_on(async { client.request("mvm_estimateGasPublishModule", params).await });
-
-        let estimated_gas = response.with_context(|| "RPC result failure")?;
+        let account_id = conn.account_id(self.account_id.as_ref())?;
+        let params = rpc_params![account_id, script_tx];
+        let estimated_gas = Estimation::request(conn, "mvm_estimateGasPublishModule", params)?;
 
-        println!("{estimated_gas}");
+        self.estimation_args
+            .print(&estimated_gas, &self.module_path, Some(conn), ctx)?;
 
         Ok(())
     }
@@ -44,30 +45,31 @@ impl EstimateGasPublishModule {
 #[derive(Parser, Debug)]
 #[clap(about = "Estimate gas for publishing a bundle")]
 pub struct EstimateGasPublishBundle {
-    #[clap(short, long, help = "Account ID in the SS58 format")]
-    account_id: String,
+    #[clap(
+        short,
+        long,
+        help = "Account ID in the SS58 format (by default the network's account ID)"
+    )]
+    account_id: Option<String>,
 
     #[clap(short, long, help = "Path to the bundle (compiled by the smove)")]
     bundle_path: PathBuf,
+
+    #[clap(flatten)]
+    estimation_args: EstimationArgs,
 }
 
 impl EstimateGasPublishBundle {
     /// Executes the command.
-    pub fn execute(&self, url: &Url) -> Result<()> {
+    pub fn execute(&self, conn: &Connection, ctx: &RunContext) -> Result<()> {
         let script_tx = read_bytes(&self.bundle_path)?;
 
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()?;
-
-        let client = HttpClientBuilder::default().build(url)?;
-        let params = rpc_params![&self.account_id, script_tx];
-        let response: Result<Estimation, _> =
-            rt.block_on(async { client.request("mvm_estimateGasPublishBundle", params).await });
-
-        let estimated_gas = response.with_context(|| "RPC result failure")?;
+        let account_id = conn.account_id(self.account_id.as_ref())?;
+        let params = rpc_params![account_id, script_tx];
+        let estimated_gas = Estimation::request(conn, "mvm_estimateGasPublishBundle", params)?;
 
-        println!("Estimated gas: {estimated_gas}");
+        self.estimation_args
+            .print(&estimated_gas, &self.bundle_path, Some(conn), ctx)?;
 
         Ok(())
     }
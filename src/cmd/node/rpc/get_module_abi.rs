@@ -1,10 +1,8 @@
-use anyhow::{Context, Result};
+use crate::cmd::node::client::Connection;
+use anyhow::Result;
 use clap::Parser;
-use jsonrpsee::core::client::ClientT;
-use jsonrpsee::http_client::HttpClientBuilder;
 use jsonrpsee::rpc_params;
 use move_vm_backend_common::abi::ModuleAbi;
-use url::Url;
 
 /// Estimate gas for publishing modules.
 #[derive(Parser, Debug)]
@@ -18,17 +16,12 @@ pub struct GetModuleAbi {
 
 impl GetModuleAbi {
     /// Executes the command.
-    pub fn execute(&self, url: &Url) -> Result<()> {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()?;
-
-        let client = HttpClientBuilder::default().build(url)?;
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
         let params = rpc_params![&self.address, &self.name];
-        let response: Result<Option<ModuleAbi>, _> =
-            rt.block_on(async { client.request("mvm_getModuleABI", params).await });
-
-        let module_abi = response.with_context(|| "RPC result failure")?;
+        let module_abi: Option<ModuleAbi> = conn.block_on(async {
+            let client = conn.client().await?;
+            client.request("mvm_getModuleABI", params).await
+        })?;
 
         println!("Module ABI: {module_abi:?}");
 
@@ -1,50 +1,451 @@
 //! List of RPC commands.
 
+pub(super) mod call;
+pub(crate) mod estimate_all;
 pub(super) mod estimate_gas_execute;
 pub(super) mod estimate_gas_publish;
 pub(super) mod get_module_abi;
 
+use crate::cmd::metadata::ArtifactMetadata;
+use crate::cmd::node::client::Connection;
+use crate::cmd::script_args::args::HexEncodedBytes;
+use crate::explain::{explain_abort_code, script_callees, status_description};
+use crate::run_context::RunContext;
+use anyhow::{Context, Error, Result};
+use clap::Parser;
+use jsonrpsee::core::params::ArrayParams;
+use jsonrpsee::rpc_params;
+use move_core_types::language_storage::ModuleId;
 use move_core_types::vm_status::StatusCode;
-use serde::Deserialize;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
 
-#[derive(Debug, Default, Copy, Clone, Deserialize)]
-#[allow(unused)]
-struct Weight {
+/// Runtime API method converting the weight into a fee.
+pub(crate) const QUERY_WEIGHT_TO_FEE: &str = "TransactionPaymentApi_query_weight_to_fee";
+
+#[derive(Debug, Default, Copy, Clone, Deserialize, Serialize, Encode, Decode)]
+pub(crate) struct Weight {
     /// The weight of computational time used based on some reference hardware.
-    ref_time: u64,
+    #[codec(compact)]
+    pub(crate) ref_time: u64,
     /// The weight of storage space used by proof of validity.
-    proof_size: u64,
+    #[codec(compact)]
+    pub(crate) proof_size: u64,
 }
 
 /// Gas estimation information.
-#[derive(Deserialize)]
-struct Estimation {
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct Estimation {
     /// Gas used.
-    gas_used: u64,
+    pub(crate) gas_used: u64,
     /// Status code for the MoveVM execution.
-    vm_status_code: StatusCode,
+    pub(crate) vm_status_code: StatusCode,
     /// Substrate weight required for the complete extrinsic cost combined with the variable
     /// gas indicated in the `Estimation` struct.
-    total_weight_including_gas_used: Weight,
+    ///
+    /// Only the node knows the weight, so it's missing for the local estimations.
+    pub(crate) total_weight_including_gas_used: Option<Weight>,
+    /// Abort code of the MoveVM execution, provided only by nodes which report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    abort_code: Option<u64>,
+    /// Modules which could have aborted the execution, all modules if unknown.
+    #[serde(skip)]
+    abort_candidates: Option<BTreeSet<ModuleId>>,
+}
+
+/// Gas estimation information with the explanation of the failure.
+pub(crate) struct ExplainedEstimation<'a> {
+    /// Gas estimation.
+    estimation: &'a Estimation,
+    /// Context providing the error descriptions.
+    ctx: &'a RunContext,
+}
+
+impl fmt::Display for ExplainedEstimation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.estimation)?;
+
+        let status = self.estimation.vm_status_code;
+        if self.estimation.is_executed() {
+            return Ok(());
+        }
+
+        write!(f, "\n    explanation: {}", status_description(status))?;
+
+        if status == StatusCode::ABORTED {
+            match self.estimation.abort_code {
+                Some(code) => {
+                    let error_maps = self.ctx.error_maps();
+                    let candidates = self.estimation.abort_candidates.as_ref();
+                    write!(
+                        f,
+                        "\n    {}",
+                        explain_abort_code(code, candidates, &error_maps)
+                    )?;
+                }
+                None => write!(
+                    f,
+                    "\n    abort code: unavailable, the node doesn't report it (estimate with `smove estimate --offline` to get it)"
+                )?,
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl fmt::Display for Estimation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let (gas_used, weight) = if matches!(self.vm_status_code, StatusCode::EXECUTED) {
-            (self.gas_used, self.total_weight_including_gas_used)
-        } else {
-            (0, Weight::default())
+        if !self.is_executed() {
+            // The failed executions don't use gas nor have a meaningful weight.
+            writeln!(f, "Gas estimation:\n    used gas: 0")?;
+            writeln!(f, "    total extrinsic weight cost: n/a")?;
+            return write!(f, "    vm_status_code: {:?}", self.vm_status_code);
+        }
+
+        writeln!(f, "Gas estimation:\n    used gas: {}", self.gas_used)?;
+        match self.total_weight_including_gas_used {
+            Some(weight) => writeln!(
+                f,
+                "    total extrinsic weight cost with the above gas:
+    \tref_time: {}
+    \tproof_size: {}",
+                weight.ref_time, weight.proof_size
+            )?,
+            None => writeln!(
+                f,
+                "    total extrinsic weight cost: unknown for the local estimation"
+            )?,
+        }
+        write!(f, "    vm_status_code: {:?}", self.vm_status_code)
+    }
+}
+
+impl Estimation {
+    /// Create a new instance for the local estimation.
+    pub(crate) fn new(
+        gas_used: u64,
+        vm_status_code: StatusCode,
+        abort_code: Option<u64>,
+        abort_location: Option<ModuleId>,
+    ) -> Self {
+        Self {
+            gas_used,
+            vm_status_code,
+            total_weight_including_gas_used: None,
+            abort_code,
+            abort_candidates: abort_location.map(|location| BTreeSet::from([location])),
+        }
+    }
+
+    /// Restricts the modules which could have aborted the script transaction to the ones it calls.
+    pub(crate) fn set_script_callees(&mut self, ctx: &RunContext, script_tx: &[u8]) -> Result<()> {
+        if self.abort_code.is_some() && self.abort_candidates.is_none() {
+            self.abort_candidates = Some(script_callees(ctx, script_tx)?);
+        }
+
+        Ok(())
+    }
+
+    /// Requests the gas estimation from the node using the given RPC method.
+    pub(crate) fn request(conn: &Connection, method: &str, params: ArrayParams) -> Result<Self> {
+        conn.block_on(async {
+            let client = conn.client().await?;
+            client.request(method, params).await
+        })
+    }
+
+    /// Returns the estimation with the explanation of the failure.
+    pub(crate) fn explained<'a>(&'a self, ctx: &'a RunContext) -> ExplainedEstimation<'a> {
+        ExplainedEstimation {
+            estimation: self,
+            ctx,
+        }
+    }
+
+    /// Asks the node to convert the total weight into a fee in the chain's native token.
+    pub(crate) fn fee_quote(&self, conn: &Connection) -> Result<TokenAmount> {
+        let weight = self.total_weight_including_gas_used.ok_or(Error::msg(
+            "The fee can't be quoted without the weight from the node",
+        ))?;
+        let weight = HexEncodedBytes::from(weight.encode());
+        let (fee, properties) = conn.block_on(async {
+            let client = conn.client().await?;
+
+            let fee: String = client
+                .request(
+                    "state_call",
+                    rpc_params![QUERY_WEIGHT_TO_FEE, weight.to_string()],
+                )
+                .await?;
+            let properties: serde_json::Value =
+                client.request("system_properties", rpc_params![]).await?;
+
+            Ok::<_, Error>((fee, properties))
+        })?;
+
+        let fee = HexEncodedBytes::from_str(&fee)?.into_inner();
+        let fee =
+            u128::decode(&mut &fee[..]).with_context(|| "Invalid fee returned by the node")?;
+
+        Ok(TokenAmount::new(fee, &properties))
+    }
+
+    /// Returns the gas limit with the safety margin added to the used gas.
+    pub(crate) fn recommended_gas_limit(&self, margin: GasMargin) -> u64 {
+        let margin = match margin {
+            GasMargin::Percent(percent) => self.gas_used.saturating_mul(percent) / 100,
+            GasMargin::Absolute(gas) => gas,
+        };
+
+        self.gas_used.saturating_add(margin)
+    }
+
+    /// Returns true if the MoveVM execution was successful.
+    pub(crate) fn is_executed(&self) -> bool {
+        matches!(self.vm_status_code, StatusCode::EXECUTED)
+    }
+}
+
+/// Safety margin added to the estimated gas.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum GasMargin {
+    /// Margin in percent of the estimated gas, e.g. `10%`.
+    Percent(u64),
+    /// Absolute amount of gas, e.g. `5000`.
+    Absolute(u64),
+}
+
+impl FromStr for GasMargin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix('%') {
+            Some(percent) => Ok(Self::Percent(percent.trim().parse().map_err(Error::msg)?)),
+            None => Ok(Self::Absolute(s.trim().parse().map_err(Error::msg)?)),
+        }
+    }
+}
+
+/// Output options for the gas estimation commands.
+#[derive(Parser, Debug)]
+pub struct EstimationArgs {
+    #[clap(
+        long,
+        help = "Convert the estimated weight into a fee in the native token"
+    )]
+    fee: bool,
+
+    #[clap(
+        long,
+        help = "Safety margin for the recommended gas limit, in percent (e.g. 10%) or absolute (e.g. 5000)"
+    )]
+    gas_margin: Option<GasMargin>,
+
+    #[clap(
+        long,
+        requires = "gas-margin",
+        help = "Write the recommended gas limit into the artifact's metadata file (<ARTIFACT>.json)"
+    )]
+    write_metadata: bool,
+}
+
+impl EstimationArgs {
+    /// Prints the estimation with the requested details.
+    ///
+    /// The fee is quoted by the node, so it's unavailable without the connection.
+    pub(crate) fn print(
+        &self,
+        estimation: &Estimation,
+        artifact_path: &Path,
+        conn: Option<&Connection>,
+        ctx: &RunContext,
+    ) -> Result<()> {
+        println!("{}", estimation.explained(ctx));
+
+        if !estimation.is_executed() {
+            return Ok(());
+        }
+
+        if self.fee {
+            let conn = conn.ok_or(Error::msg("The fee can be quoted only by the node"))?;
+            println!("    fee: {}", estimation.fee_quote(conn)?);
+        }
+
+        if let Some(margin) = self.gas_margin {
+            let gas_limit = estimation.recommended_gas_limit(margin);
+            println!("    recommended gas_limit: {gas_limit}");
+
+            if self.write_metadata {
+                let mut metadata = ArtifactMetadata::load(artifact_path)?;
+                metadata.gas_limit = Some(gas_limit);
+                let path = metadata.store(artifact_path)?;
+                println!("Gas limit is written to:\n{}", path.display());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Amount in the chain's native token.
+pub(crate) struct TokenAmount {
+    /// Amount in the smallest units.
+    amount: u128,
+    /// Number of decimals of the token.
+    decimals: u32,
+    /// Symbol of the token.
+    symbol: String,
+}
+
+impl TokenAmount {
+    /// Create a new instance with the token details from the `system_properties` RPC call.
+    pub(crate) fn new(amount: u128, properties: &serde_json::Value) -> Self {
+        // Chains with multiple tokens return arrays, the first one is the native token.
+        let first = |value: &serde_json::Value| match value {
+            serde_json::Value::Array(values) => values.first().cloned(),
+            value => Some(value.clone()),
+        };
+
+        let decimals = properties
+            .get("tokenDecimals")
+            .and_then(first)
+            .and_then(|value| value.as_u64())
+            .unwrap_or_default() as u32;
+        let symbol = properties
+            .get("tokenSymbol")
+            .and_then(first)
+            .and_then(|value| value.as_str().map(String::from))
+            .unwrap_or_default();
+
+        Self {
+            amount,
+            decimals,
+            symbol,
+        }
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Decimals beyond the u128 range can't be real, print the raw units only.
+        let Some(unit) = 10u128.checked_pow(self.decimals) else {
+            return write!(f, "{} units", self.amount);
         };
+        let (integer, fraction) = (self.amount / unit, self.amount % unit);
+
+        write!(f, "{integer}")?;
+        if self.decimals > 0 {
+            write!(f, ".{fraction:0width$}", width = self.decimals as usize)?;
+        }
+
+        if !self.symbol.is_empty() {
+            write!(f, " {}", self.symbol)?;
+        }
+
+        write!(f, " ({} units)", self.amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Returns the node's estimation of a successful execution.
+    fn executed(gas_used: u64) -> Estimation {
+        serde_json::from_value(json!({
+            "gas_used": gas_used,
+            "vm_status_code": StatusCode::EXECUTED as u64,
+            "total_weight_including_gas_used": { "ref_time": 0, "proof_size": 0 },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn gas_margin_in_percent() {
+        let margin = GasMargin::from_str("10%").unwrap();
+
+        assert!(matches!(margin, GasMargin::Percent(10)));
+        assert_eq!(executed(1000).recommended_gas_limit(margin), 1100);
+    }
+
+    #[test]
+    fn absolute_gas_margin() {
+        let margin = GasMargin::from_str(" 5000 ").unwrap();
+
+        assert!(matches!(margin, GasMargin::Absolute(5000)));
+        assert_eq!(executed(1000).recommended_gas_limit(margin), 6000);
+    }
+
+    #[test]
+    fn invalid_gas_margin() {
+        assert!(GasMargin::from_str("ten%").is_err());
+        assert!(GasMargin::from_str("-5").is_err());
+        assert!(GasMargin::from_str("").is_err());
+    }
+
+    #[test]
+    fn recommended_gas_limit_saturates() {
+        assert_eq!(
+            executed(u64::MAX).recommended_gas_limit(GasMargin::Absolute(1)),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn failed_estimation_has_no_weight() {
+        let estimation: Estimation = serde_json::from_value(json!({
+            "gas_used": 1000,
+            "vm_status_code": StatusCode::ABORTED as u64,
+            "total_weight_including_gas_used": { "ref_time": 1, "proof_size": 1 },
+            "abort_code": 1,
+        }))
+        .unwrap();
+        let display = estimation.to_string();
+
+        assert!(display.contains("used gas: 0"));
+        assert!(display.contains("total extrinsic weight cost: n/a"));
+        assert!(!display.contains("ref_time"));
+    }
+
+    #[test]
+    fn token_amount_display() {
+        let properties = json!({ "tokenDecimals": 12, "tokenSymbol": "UNIT" });
+
+        assert_eq!(
+            TokenAmount::new(1_500_000_000_000, &properties).to_string(),
+            "1.500000000000 UNIT (1500000000000 units)"
+        );
+        assert_eq!(
+            TokenAmount::new(42, &properties).to_string(),
+            "0.000000000042 UNIT (42 units)"
+        );
+    }
+
+    #[test]
+    fn token_amount_display_for_multiple_tokens() {
+        let properties = json!({ "tokenDecimals": [2, 18], "tokenSymbol": ["DOT", "ETH"] });
+
+        assert_eq!(
+            TokenAmount::new(150, &properties).to_string(),
+            "1.50 DOT (150 units)"
+        );
+    }
+
+    #[test]
+    fn token_amount_display_without_decimals() {
+        assert_eq!(TokenAmount::new(7, &json!({})).to_string(), "7 (7 units)");
+    }
+
+    #[test]
+    fn token_amount_display_with_decimals_overflowing_u128() {
+        let properties = json!({ "tokenDecimals": 40, "tokenSymbol": "UNIT" });
 
-        write!(
-            f,
-            "Gas estimation:
-    used gas: {gas_used}
-    total extrinsic weight cost with the above gas:
-    \t{weight:?}
-    vm_status_code: {:?}",
-            self.vm_status_code
-        )
+        assert_eq!(TokenAmount::new(7, &properties).to_string(), "7 units");
     }
 }
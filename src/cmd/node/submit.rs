@@ -0,0 +1,355 @@
+//! Signing and submission of pallet-move extrinsics.
+
+use crate::cmd::metadata::ArtifactMetadata;
+use crate::cmd::node::client::Connection;
+use crate::cmd::node::rpc::{Estimation, GasMargin};
+use crate::cmd::read_bytes;
+use anyhow::{Error, Result};
+use clap::Parser;
+use jsonrpsee::core::params::ArrayParams;
+use jsonrpsee::rpc_params;
+use std::path::{Path, PathBuf};
+use subxt::dynamic::Value;
+use subxt::tx::{DynamicPayload, TxInBlock, TxProgress, TxStatus};
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_signer::sr25519::{dev, Keypair};
+use subxt_signer::SecretUri;
+
+/// Name of pallet-move within the runtime.
+pub(crate) const PALLET_NAME: &str = "MoveModule";
+
+/// Sign and submit pallet-move extrinsics.
+#[derive(clap::Subcommand)]
+pub enum Submit {
+    /// Execute a script transaction.
+    #[clap(about = "Execute a script transaction")]
+    Execute {
+        #[clap(flatten)]
+        cmd: SubmitExecute,
+    },
+
+    /// Publish a module.
+    #[clap(about = "Publish a module")]
+    PublishModule {
+        #[clap(flatten)]
+        cmd: SubmitPublishModule,
+    },
+
+    /// Publish a bundle.
+    #[clap(about = "Publish a bundle")]
+    PublishBundle {
+        #[clap(flatten)]
+        cmd: SubmitPublishBundle,
+    },
+}
+
+impl Submit {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        conn.check_websocket()?;
+
+        match self {
+            Self::Execute { cmd } => cmd.execute(conn),
+            Self::PublishModule { cmd } => cmd.execute(conn),
+            Self::PublishBundle { cmd } => cmd.execute(conn),
+        }
+    }
+}
+
+/// Signing key options.
+#[derive(Parser, Debug)]
+pub struct SignerArgs {
+    #[clap(
+        long,
+        help = "Secret URI of the sr25519 signing key (mnemonic, hex seed or derivation path like //Alice)",
+        conflicts_with = "dev-account",
+        required_unless_present = "dev-account"
+    )]
+    seed: Option<String>,
+
+    #[clap(
+        long,
+        help = "Development account used as the signer [alice, bob, charlie, dave, eve, ferdie]"
+    )]
+    dev_account: Option<String>,
+}
+
+impl SignerArgs {
+    /// Returns the sr25519 key pair for signing extrinsics.
+    fn keypair(&self) -> Result<Keypair> {
+        if let Some(ref seed) = self.seed {
+            let uri = seed
+                .parse::<SecretUri>()
+                .map_err(|e| Error::msg(format!("Invalid secret URI: {e}")))?;
+            return Keypair::from_uri(&uri)
+                .map_err(|e| Error::msg(format!("Invalid signing key: {e}")));
+        }
+
+        // Clap makes sure one of the options is present.
+        match self.dev_account.as_deref().unwrap_or_default() {
+            "alice" => Ok(dev::alice()),
+            "bob" => Ok(dev::bob()),
+            "charlie" => Ok(dev::charlie()),
+            "dave" => Ok(dev::dave()),
+            "eve" => Ok(dev::eve()),
+            "ferdie" => Ok(dev::ferdie()),
+            wrong_account => Err(Error::msg(format!(
+                "Unknown development account '{wrong_account}'"
+            ))),
+        }
+    }
+}
+
+/// Gas limit options.
+#[derive(Parser, Debug)]
+pub struct GasArgs {
+    #[clap(
+        long,
+        help = "Gas limit for the extrinsic (by default the gas limit from the artifact's metadata or the estimated gas plus the gas margin)"
+    )]
+    gas_limit: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Safety margin added to the estimated gas, in percent (e.g. 10%) or absolute (e.g. 5000)",
+        default_value = "10%"
+    )]
+    gas_margin: GasMargin,
+}
+
+impl GasArgs {
+    /// Returns the gas limit, using the artifact's metadata or the node's gas estimation if it
+    /// wasn't explicitly set.
+    fn gas_limit(
+        &self,
+        conn: &Connection,
+        artifact_path: &Path,
+        method: &str,
+        params: ArrayParams,
+    ) -> Result<u64> {
+        if let Some(gas_limit) = self.gas_limit {
+            return Ok(gas_limit);
+        }
+
+        if let Some(gas_limit) = ArtifactMetadata::load(artifact_path)?.gas_limit {
+            println!("Using gas limit from the metadata: {gas_limit}");
+            return Ok(gas_limit);
+        }
+
+        let estimation = Estimation::request(conn, method, params)?;
+        if !estimation.is_executed() {
+            return Err(Error::msg(format!(
+                "Gas estimation failed with the status code {:?}",
+                estimation.vm_status_code
+            )));
+        }
+
+        let gas_limit = estimation.recommended_gas_limit(self.gas_margin);
+        println!(
+            "Estimated gas: {}, using gas limit: {gas_limit}",
+            estimation.gas_used
+        );
+
+        Ok(gas_limit)
+    }
+}
+
+/// Execute a script transaction.
+#[derive(Parser, Debug)]
+#[clap(about = "Execute a script transaction")]
+pub struct SubmitExecute {
+    /// Path to the script transaction (compiled by the smove create-transaction).
+    #[clap(short, long)]
+    script_transaction_path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Maximum amount of balance the script is allowed to transfer (by default the cash limit from the artifact's metadata)"
+    )]
+    cash_limit: Option<u128>,
+
+    #[clap(flatten)]
+    gas: GasArgs,
+
+    #[clap(flatten)]
+    signer: SignerArgs,
+}
+
+impl SubmitExecute {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        let script_tx = read_bytes(&self.script_transaction_path)?;
+        let signer = self.signer.keypair()?;
+
+        let params = rpc_params![&script_tx];
+        let gas_limit = self.gas.gas_limit(
+            conn,
+            &self.script_transaction_path,
+            "mvm_estimateGasExecuteScript",
+            params,
+        )?;
+
+        let cash_limit = match self.cash_limit {
+            Some(cash_limit) => cash_limit,
+            None => ArtifactMetadata::load(&self.script_transaction_path)?
+                .cash_limit
+                .ok_or(Error::msg(
+                    "Cash limit is required: use --cash-limit or create the transaction with smove create-transaction --write-metadata",
+                ))?,
+        };
+
+        let call = execute_call(script_tx, gas_limit, cash_limit);
+
+        sign_and_submit(conn, &signer, call)
+    }
+}
+
+/// Publish a module.
+#[derive(Parser, Debug)]
+#[clap(about = "Publish a module")]
+pub struct SubmitPublishModule {
+    #[clap(short, long, help = "Path to the module (compiled by the smove)")]
+    module_path: PathBuf,
+
+    #[clap(flatten)]
+    gas: GasArgs,
+
+    #[clap(flatten)]
+    signer: SignerArgs,
+}
+
+impl SubmitPublishModule {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        let module = read_bytes(&self.module_path)?;
+        let signer = self.signer.keypair()?;
+
+        let account_id = signer.public_key().to_account_id().to_string();
+        let params = rpc_params![account_id, &module];
+        let gas_limit = self.gas.gas_limit(
+            conn,
+            &self.module_path,
+            "mvm_estimateGasPublishModule",
+            params,
+        )?;
+
+        let call = publish_module_call(module, gas_limit);
+
+        sign_and_submit(conn, &signer, call)
+    }
+}
+
+/// Publish a bundle.
+#[derive(Parser, Debug)]
+#[clap(about = "Publish a bundle")]
+pub struct SubmitPublishBundle {
+    #[clap(short, long, help = "Path to the bundle (compiled by the smove)")]
+    bundle_path: PathBuf,
+
+    #[clap(flatten)]
+    gas: GasArgs,
+
+    #[clap(flatten)]
+    signer: SignerArgs,
+}
+
+impl SubmitPublishBundle {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        let bundle = read_bytes(&self.bundle_path)?;
+        let signer = self.signer.keypair()?;
+
+        let account_id = signer.public_key().to_account_id().to_string();
+        let params = rpc_params![account_id, &bundle];
+        let gas_limit = self.gas.gas_limit(
+            conn,
+            &self.bundle_path,
+            "mvm_estimateGasPublishBundle",
+            params,
+        )?;
+
+        let call = publish_bundle_call(bundle, gas_limit);
+
+        sign_and_submit(conn, &signer, call)
+    }
+}
+
+/// Creates the pallet-move `execute` call.
+pub(crate) fn execute_call(script_tx: Vec<u8>, gas_limit: u64, cash_limit: u128) -> DynamicPayload {
+    subxt::dynamic::tx(
+        PALLET_NAME,
+        "execute",
+        vec![
+            Value::from_bytes(script_tx),
+            Value::u128(gas_limit.into()),
+            Value::u128(cash_limit),
+        ],
+    )
+}
+
+/// Creates the pallet-move `publish_module` call.
+pub(crate) fn publish_module_call(module: Vec<u8>, gas_limit: u64) -> DynamicPayload {
+    subxt::dynamic::tx(
+        PALLET_NAME,
+        "publish_module",
+        vec![Value::from_bytes(module), Value::u128(gas_limit.into())],
+    )
+}
+
+/// Creates the pallet-move `publish_module_bundle` call.
+pub(crate) fn publish_bundle_call(bundle: Vec<u8>, gas_limit: u64) -> DynamicPayload {
+    subxt::dynamic::tx(
+        PALLET_NAME,
+        "publish_module_bundle",
+        vec![Value::from_bytes(bundle), Value::u128(gas_limit.into())],
+    )
+}
+
+/// Signs the call, submits it to the node and waits until it's included in a block.
+fn sign_and_submit(conn: &Connection, signer: &Keypair, call: DynamicPayload) -> Result<()> {
+    conn.block_on(async {
+        let api = conn.subxt_client().await?;
+
+        let progress = api
+            .tx()
+            .sign_and_submit_then_watch_default(&call, signer)
+            .await?;
+        println!("Extrinsic submitted: {:?}", progress.extrinsic_hash());
+
+        let in_block = wait_for_in_block(progress).await?;
+        let events = in_block.wait_for_success().await?;
+        println!("Extrinsic included in block: {:?}", in_block.block_hash());
+
+        for event in events.iter() {
+            let event = event?;
+            if event.pallet_name() == PALLET_NAME {
+                println!("Event: {}::{}", event.pallet_name(), event.variant_name());
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Waits until the extrinsic is included in a best or finalized block.
+async fn wait_for_in_block(
+    mut progress: TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<TxInBlock<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
+    while let Some(status) = progress.next().await {
+        match status? {
+            TxStatus::InBestBlock(in_block) | TxStatus::InFinalizedBlock(in_block) => {
+                return Ok(in_block)
+            }
+            TxStatus::Error { message }
+            | TxStatus::Invalid { message }
+            | TxStatus::Dropped { message } => {
+                return Err(Error::msg(format!("Extrinsic rejected: {message}")))
+            }
+            _ => continue,
+        }
+    }
+
+    Err(Error::msg(
+        "The node stopped reporting the extrinsic's status before its inclusion in a block",
+    ))
+}
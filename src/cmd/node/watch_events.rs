@@ -0,0 +1,49 @@
+use crate::cmd::node::{client::Connection, submit::PALLET_NAME};
+use anyhow::Result;
+use clap::Parser;
+
+/// Watch pallet-move events.
+#[derive(Parser, Debug)]
+#[clap(about = "Subscribe to new blocks and print pallet-move events")]
+pub struct WatchEvents {
+    #[clap(long, help = "Watch only finalized blocks")]
+    finalized: bool,
+}
+
+impl WatchEvents {
+    /// Executes the command.
+    pub fn execute(&self, conn: &Connection) -> Result<()> {
+        conn.block_on(async {
+            let api = conn.subxt_client().await?;
+
+            let mut blocks = if self.finalized {
+                api.blocks().subscribe_finalized().await?
+            } else {
+                api.blocks().subscribe_best().await?
+            };
+
+            println!("Watching pallet-move events at {}", conn.url);
+
+            while let Some(block) = blocks.next().await {
+                let block = block?;
+                let events = block.events().await?;
+
+                for event in events.iter() {
+                    let event = event?;
+                    if event.pallet_name() != PALLET_NAME {
+                        continue;
+                    }
+
+                    println!(
+                        "#{} {}: {}",
+                        block.number(),
+                        event.variant_name(),
+                        event.field_values()?
+                    );
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
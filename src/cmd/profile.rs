@@ -0,0 +1,83 @@
+use crate::cmd::estimate::offline_storage;
+use crate::cmd::read_bytes;
+use crate::local_vm::{Execution, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::profiler::ProfilingGasMeter;
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use clap::Parser;
+use move_vm_backend_common::types::ScriptTransaction;
+use std::path::PathBuf;
+
+/// Profile the gas used per function by a script transaction with the embedded MoveVM.
+#[derive(Parser, Debug)]
+#[clap(about = "smove profile")]
+pub struct Profile {
+    /// Path to the script transaction (compiled by the smove create-transaction).
+    #[clap(short, long)]
+    script_transaction_path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Execute against the devnet or forked state in the directory instead of the package's builds"
+    )]
+    state: Option<PathBuf>,
+
+    #[clap(
+        short,
+        long,
+        help = "Write the folded stacks to the file, e.g. for inferno-flamegraph or flamegraph.pl"
+    )]
+    output: Option<PathBuf>,
+
+    #[clap(
+        long,
+        default_value = "10",
+        help = "Number of the most expensive functions to show"
+    )]
+    top: usize,
+
+    #[clap(
+        long,
+        help = "Maximum gas for the execution (by default unlimited in practice)"
+    )]
+    gas_limit: Option<u64>,
+}
+
+impl Profile {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let tx: ScriptTransaction = bcs::from_bytes(&read_bytes(&self.script_transaction_path)?)
+            .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+        let gas_limit = self.gas_limit.unwrap_or(ESTIMATION_GAS_LIMIT);
+
+        let vm = LocalVm::new(ctx)?;
+        let storage = offline_storage(ctx, &vm, self.state.as_deref())?;
+
+        let mut gas_meter = ProfilingGasMeter::new(vm.gas_status(gas_limit));
+        let output = vm.execute_script_metered(&storage, &tx, &mut gas_meter);
+        let (gas_status, profile) = gas_meter.finish();
+        let gas_used = gas_limit - u64::from(gas_status.remaining_gas());
+        let execution = Execution::new(gas_used, output);
+
+        println!("{}", execution.estimation().explained(ctx));
+
+        let functions = profile.functions();
+        println!(
+            "{:<60} {:>8} {:>14} {:>14}",
+            "Function", "Calls", "Self gas", "Total gas"
+        );
+        for function in functions.iter().take(self.top) {
+            println!(
+                "{:<60} {:>8} {:>14} {:>14}",
+                function.name, function.calls, function.self_gas, function.total_gas
+            );
+        }
+
+        if let Some(ref path) = self.output {
+            profile.write_folded(path)?;
+            println!("Folded stacks written to {}", path.display());
+        }
+
+        Ok(())
+    }
+}
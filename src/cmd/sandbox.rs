@@ -0,0 +1,262 @@
+//! smove's additions to the move-cli `sandbox` subcommands for running the shipped artifacts.
+//!
+//! The move-cli subcommands can't be extended with the derive API, so the additional
+//! subcommands are attached to the `sandbox` command at runtime and dispatched before the
+//! regular parsing.
+
+use crate::cmd::read_bytes;
+use crate::local_vm::{bundle_address, Execution, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use clap::{ArgMatches, FromArgMatches, Parser, Subcommand};
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_core_types::effects::Op;
+use move_core_types::language_storage::ModuleId;
+use move_package::compilation::package_layout::CompiledPackageLayout;
+use move_vm_backend_common::types::ScriptTransaction;
+use std::path::PathBuf;
+
+/// Name of the move-cli sandbox command.
+const SANDBOX_COMMAND: &str = "sandbox";
+
+/// Argument ID of the move-cli sandbox's storage directory.
+const STORAGE_DIR_ARG: &str = "storage-dir";
+
+/// smove's sandbox subcommands.
+#[derive(Subcommand)]
+pub enum SandboxCmd {
+    /// Publish a bundle.
+    #[clap(about = "Publish the bundle's modules (compiled by the smove bundle) in order")]
+    PublishBundle {
+        #[clap(flatten)]
+        cmd: PublishBundle,
+    },
+
+    /// Run a script transaction.
+    #[clap(about = "Run the script transaction (compiled by the smove create-transaction)")]
+    RunTransaction {
+        #[clap(flatten)]
+        cmd: RunTransaction,
+    },
+}
+
+/// smove's sandbox subcommand with the sandbox options.
+pub struct Sandbox {
+    /// Directory with the sandbox storage.
+    storage_dir: PathBuf,
+    /// Command option.
+    cmd: SandboxCmd,
+}
+
+/// Adds smove's subcommands to the move-cli sandbox command.
+pub fn augment_sandbox(mut command: clap::Command<'static>) -> clap::Command<'static> {
+    if let Some(sandbox) = command.find_subcommand_mut(SANDBOX_COMMAND) {
+        *sandbox = SandboxCmd::augment_subcommands(sandbox.clone());
+    }
+
+    command
+}
+
+impl Sandbox {
+    /// Returns smove's sandbox subcommand if it was chosen.
+    pub fn from_matches(matches: &ArgMatches) -> Result<Option<Self>> {
+        let Some((SANDBOX_COMMAND, sandbox)) = matches.subcommand() else {
+            return Ok(None);
+        };
+        if !sandbox
+            .subcommand_name()
+            .is_some_and(SandboxCmd::has_subcommand)
+        {
+            return Ok(None);
+        }
+
+        let storage_dir = sandbox
+            .get_raw(STORAGE_DIR_ARG)?
+            .and_then(|mut values| values.next())
+            .map(PathBuf::from)
+            .ok_or(Error::msg("The sandbox storage directory is not set"))?;
+
+        Ok(Some(Self {
+            storage_dir,
+            cmd: SandboxCmd::from_arg_matches(sandbox)?,
+        }))
+    }
+
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let build_dir = ctx
+            .project_root_dir
+            .join(CompiledPackageLayout::Root.path());
+        let state = OnDiskStateView::create(build_dir, self.storage_dir.clone())?;
+
+        match &self.cmd {
+            SandboxCmd::PublishBundle { cmd } => cmd.execute(ctx, &state),
+            SandboxCmd::RunTransaction { cmd } => cmd.execute(ctx, &state),
+        }
+    }
+}
+
+/// Publish a bundle.
+#[derive(Parser, Debug)]
+pub struct PublishBundle {
+    /// Path to the bundle (compiled by the smove bundle).
+    bundle_path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Maximum gas for the publication (by default unlimited in practice)"
+    )]
+    gas_budget: Option<u64>,
+}
+
+impl PublishBundle {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext, state: &OnDiskStateView) -> Result<()> {
+        let bundle = read_bytes(&self.bundle_path)?;
+        let gas_limit = self.gas_budget.unwrap_or(ESTIMATION_GAS_LIMIT);
+
+        let vm = LocalVm::new(ctx)?;
+        let execution = vm.publish_bundle(state, &bundle, bundle_address(&bundle)?, gas_limit)?;
+
+        commit(ctx, state, &execution)
+    }
+}
+
+/// Run a script transaction.
+#[derive(Parser, Debug)]
+pub struct RunTransaction {
+    /// Path to the script transaction (compiled by the smove create-transaction).
+    script_transaction_path: PathBuf,
+
+    #[clap(
+        long,
+        help = "Maximum gas for the execution (by default unlimited in practice)"
+    )]
+    gas_budget: Option<u64>,
+}
+
+impl RunTransaction {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext, state: &OnDiskStateView) -> Result<()> {
+        let tx: ScriptTransaction = bcs::from_bytes(&read_bytes(&self.script_transaction_path)?)
+            .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+        let gas_limit = self.gas_budget.unwrap_or(ESTIMATION_GAS_LIMIT);
+
+        let vm = LocalVm::new(ctx)?;
+        let execution = vm.execute_script(state, &tx, gas_limit)?;
+
+        commit(ctx, state, &execution)
+    }
+}
+
+/// Saves the changes and events of a successful execution into the sandbox storage and prints
+/// the result.
+fn commit(ctx: &RunContext, state: &OnDiskStateView, execution: &Execution) -> Result<()> {
+    if let Some((ref changes, ref events)) = execution.effects {
+        for (address, account) in changes.accounts() {
+            for (name, op) in account.modules() {
+                if let Op::New(blob) | Op::Modify(blob) = op {
+                    state.save_module(&ModuleId::new(*address, name.clone()), blob)?;
+                }
+            }
+            for (tag, op) in account.resources() {
+                match op {
+                    Op::New(blob) | Op::Modify(blob) => {
+                        state.save_resource(*address, tag.clone(), blob)?
+                    }
+                    Op::Delete => state.delete_resource(*address, tag.clone())?,
+                }
+            }
+        }
+
+        for (guid, sequence_number, type_tag, data) in events {
+            state.save_event(guid, *sequence_number, type_tag.clone(), data.clone())?;
+        }
+    }
+
+    println!("{}", execution.estimation().explained(ctx));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SmoveArgs, SmoveCommand};
+    use clap::CommandFactory;
+
+    /// Parses the arguments with the augmented smove command.
+    fn matches(args: &[&str]) -> ArgMatches {
+        augment_sandbox(SmoveArgs::command())
+            .try_get_matches_from(args)
+            .unwrap()
+    }
+
+    #[test]
+    fn sandbox_subcommands() {
+        // Panics on the argument IDs which don't exist in the commands.
+        augment_sandbox(SmoveArgs::command()).debug_assert();
+
+        let command = augment_sandbox(SmoveArgs::command());
+        let sandbox = command.find_subcommand(SANDBOX_COMMAND).unwrap();
+
+        for name in [
+            "publish-bundle",
+            "run-transaction",
+            "publish",
+            "run",
+            "view",
+        ] {
+            assert!(
+                sandbox.find_subcommand(name).is_some(),
+                "missing sandbox subcommand {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn smove_sandbox_subcommand() {
+        let matches = matches(&[
+            "smove",
+            "sandbox",
+            "--storage-dir",
+            "state",
+            "publish-bundle",
+            "package.mvb",
+            "--gas-budget",
+            "100",
+        ]);
+        let sandbox = Sandbox::from_matches(&matches).unwrap().unwrap();
+
+        assert_eq!(sandbox.storage_dir, PathBuf::from("state"));
+        let SandboxCmd::PublishBundle { cmd } = sandbox.cmd else {
+            panic!("unexpected sandbox subcommand");
+        };
+        assert_eq!(cmd.bundle_path, PathBuf::from("package.mvb"));
+        assert_eq!(cmd.gas_budget, Some(100));
+
+        let matches = matches(&["smove", "sandbox", "run-transaction", "script.mvt"]);
+        let sandbox = Sandbox::from_matches(&matches).unwrap().unwrap();
+        let SandboxCmd::RunTransaction { cmd } = sandbox.cmd else {
+            panic!("unexpected sandbox subcommand");
+        };
+        assert_eq!(cmd.script_transaction_path, PathBuf::from("script.mvt"));
+        assert_eq!(cmd.gas_budget, None);
+    }
+
+    #[test]
+    fn move_cli_sandbox_subcommands_are_untouched() {
+        for args in [
+            &["smove", "sandbox", "publish"][..],
+            &["smove", "sandbox", "view", "storage/resource.bcs"][..],
+            &["smove", "sandbox", "clean"][..],
+            &["smove", "build"][..],
+        ] {
+            let matches = matches(args);
+            assert!(Sandbox::from_matches(&matches).unwrap().is_none());
+
+            let SmoveArgs { cmd, .. } = SmoveArgs::from_arg_matches(&matches).unwrap();
+            assert!(matches!(cmd, SmoveCommand::MoveCommand(_)), "{args:?}");
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! Declarative scenarios for end-to-end testing of deployments with the embedded MoveVM.
+//!
+//! A scenario is a TOML file with steps publishing modules and bundles, executing scripts and
+//! asserting resources. The steps run in order against an in-memory storage with the package's
+//! dependencies published.
+
+use crate::cmd::devnet::effects::print_transfers;
+use crate::cmd::devnet::script_signers;
+use crate::cmd::script::script_transaction;
+use crate::cmd::script_args::args::parse_address;
+use crate::cmd::script_args::ScriptFunctionArguments;
+use crate::local_vm::{bundle_address, Execution, LocalVm, ESTIMATION_GAS_LIMIT};
+use crate::natives::{balance_natives, BalanceLedger, SharedLedger};
+use crate::run_context::RunContext;
+use anyhow::{Context, Error, Result};
+use clap::Parser;
+use move_binary_format::CompiledModule;
+use move_core_types::parser::parse_struct_tag;
+use move_core_types::resolver::ResourceResolver;
+use move_resource_viewer::MoveValueAnnotator;
+use move_vm_backend_common::types::ScriptTransaction;
+use move_vm_test_utils::InMemoryStorage;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Extension of the bundle files.
+const BUNDLE_EXTENSION: &str = "mvb";
+/// Extension of the script transaction files.
+const SCRIPT_TX_EXTENSION: &str = "mvt";
+
+/// Scenario commands.
+#[derive(clap::Subcommand)]
+pub enum Scenario {
+    /// Run a scenario.
+    #[clap(about = "Run the scenario's steps and report pass or fail per step")]
+    Run {
+        #[clap(flatten)]
+        cmd: RunScenario,
+    },
+}
+
+impl Scenario {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        match self {
+            Self::Run { cmd } => cmd.execute(ctx),
+        }
+    }
+}
+
+/// Run a scenario.
+#[derive(Parser, Debug)]
+pub struct RunScenario {
+    /// Path to the scenario file.
+    scenario_path: PathBuf,
+}
+
+/// Scenario file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    /// Initial balances of the accounts (SS58 or Move addresses).
+    #[serde(default)]
+    balances: BTreeMap<String, u128>,
+    /// Steps run in order.
+    steps: Vec<Step>,
+}
+
+/// Scenario step.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case", deny_unknown_fields)]
+enum Step {
+    /// Publish a module or a bundle.
+    Publish {
+        /// Step's name.
+        name: Option<String>,
+        /// Path to the module (.mv) or the bundle (.mvb).
+        path: PathBuf,
+        /// Account publishing the modules (by default the modules' address).
+        sender: Option<String>,
+        /// Expected status code name (by default EXECUTED).
+        expect_status: Option<String>,
+        /// Expected abort code.
+        expect_abort_code: Option<u64>,
+    },
+    /// Execute a script transaction, or a compiled script with its arguments.
+    Execute {
+        /// Step's name.
+        name: Option<String>,
+        /// Path to the script transaction (.mvt) or the compiled script (.mv).
+        path: PathBuf,
+        /// Script arguments in the smove's argument syntax, e.g. `signer:<ADDRESS>`.
+        #[serde(default)]
+        args: Vec<String>,
+        /// Script type arguments.
+        #[serde(default)]
+        type_args: Vec<String>,
+        /// Amount each signer allows the script to transfer.
+        cash_limit: Option<u128>,
+        /// Maximum gas for the execution.
+        gas_limit: Option<u64>,
+        /// Expected status code name (by default EXECUTED).
+        expect_status: Option<String>,
+        /// Expected abort code.
+        expect_abort_code: Option<u64>,
+    },
+    /// Check a resource stored under an account.
+    AssertResource {
+        /// Step's name.
+        name: Option<String>,
+        /// Account storing the resource (SS58 or Move address).
+        address: String,
+        /// Resource's struct tag.
+        tag: String,
+        /// Whether the resource must exist (by default true).
+        exists: Option<bool>,
+        /// Expected field values as printed by the resource viewer.
+        #[serde(default)]
+        fields: BTreeMap<String, String>,
+    },
+}
+
+impl Step {
+    /// Returns the step's name or a description.
+    fn name(&self) -> String {
+        match self {
+            Self::Publish { name, path, .. } => name
+                .clone()
+                .unwrap_or_else(|| format!("publish {}", path.display())),
+            Self::Execute { name, path, .. } => name
+                .clone()
+                .unwrap_or_else(|| format!("execute {}", path.display())),
+            Self::AssertResource {
+                name, address, tag, ..
+            } => name
+                .clone()
+                .unwrap_or_else(|| format!("assert {tag} under {address}")),
+        }
+    }
+}
+
+/// State of a running scenario.
+struct ScenarioRun<'a> {
+    /// Run context.
+    ctx: &'a RunContext,
+    /// Virtual machine.
+    vm: LocalVm,
+    /// Published modules and resources.
+    storage: InMemoryStorage,
+    /// Balances used by the balance natives.
+    ledger: SharedLedger,
+}
+
+impl RunScenario {
+    /// Executes the command.
+    pub fn execute(&self, ctx: &RunContext) -> Result<()> {
+        let content = fs::read_to_string(&self.scenario_path).with_context(|| {
+            format!("Failure to read filename {}", self.scenario_path.display())
+        })?;
+        let scenario: ScenarioFile = toml::from_str(&content)
+            .with_context(|| format!("Invalid scenario {}", self.scenario_path.display()))?;
+
+        let balances = scenario
+            .balances
+            .iter()
+            .map(|(account, amount)| Ok((parse_address(account)?, *amount)))
+            .collect::<Result<_>>()?;
+        let ledger = Arc::new(Mutex::new(BalanceLedger {
+            balances,
+            ..Default::default()
+        }));
+
+        let mut storage = InMemoryStorage::new();
+        LocalVm::publish_genesis_modules(&mut storage, &ctx.get_dependency_modules()?)?;
+
+        let mut run = ScenarioRun {
+            ctx,
+            vm: LocalVm::with_natives(ctx, balance_natives(ledger.clone()))?,
+            storage,
+            ledger,
+        };
+
+        let mut failed = 0;
+        for (index, step) in scenario.steps.iter().enumerate() {
+            match run.step(step) {
+                Ok(()) => println!("[PASS] {}. {}", index + 1, step.name()),
+                Err(e) => {
+                    failed += 1;
+                    println!("[FAIL] {}. {}: {e:#}", index + 1, step.name());
+                }
+            }
+        }
+
+        let total = scenario.steps.len();
+        println!("{} of {total} steps passed", total - failed);
+        if failed > 0 {
+            return Err(Error::msg(format!("{failed} of {total} steps failed")));
+        }
+
+        Ok(())
+    }
+}
+
+impl ScenarioRun<'_> {
+    /// Runs the step, returns an error if the step fails.
+    fn step(&mut self, step: &Step) -> Result<()> {
+        match step {
+            Step::Publish {
+                path,
+                sender,
+                expect_status,
+                expect_abort_code,
+                ..
+            } => {
+                let path = self.ctx.project_root_dir.join(path);
+                let bytecode = fs::read(&path)
+                    .with_context(|| format!("Failure to read filename {}", path.display()))?;
+                let is_bundle = path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION);
+
+                let sender = match sender {
+                    Some(sender) => parse_address(sender)?,
+                    None if is_bundle => bundle_address(&bytecode)?,
+                    None => *CompiledModule::deserialize(&bytecode)
+                        .map_err(|e| Error::msg(format!("Invalid module: {e:?}")))?
+                        .self_id()
+                        .address(),
+                };
+
+                let execution = if is_bundle {
+                    self.vm.publish_bundle(
+                        &self.storage,
+                        &bytecode,
+                        sender,
+                        ESTIMATION_GAS_LIMIT,
+                    )?
+                } else {
+                    self.vm
+                        .publish_module(&self.storage, bytecode, sender, ESTIMATION_GAS_LIMIT)?
+                };
+
+                self.finish(&execution, expect_status.as_deref(), *expect_abort_code)
+            }
+            Step::Execute {
+                path,
+                args,
+                type_args,
+                cash_limit,
+                gas_limit,
+                expect_status,
+                expect_abort_code,
+                ..
+            } => {
+                let path = self.ctx.project_root_dir.join(path);
+                let tx = if path
+                    .extension()
+                    .is_some_and(|ext| ext == SCRIPT_TX_EXTENSION)
+                {
+                    bcs::from_bytes(&fs::read(&path)?)
+                        .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?
+                } else {
+                    script_transaction(&path, &script_function_args(args, type_args)?)?
+                };
+
+                // The failed scripts don't change the storage, so their transfers are rolled back.
+                let snapshot = self.ledger.lock().unwrap().clone();
+                self.issue_cheques(&tx, cash_limit.unwrap_or_default())?;
+                let gas_limit = gas_limit.unwrap_or(ESTIMATION_GAS_LIMIT);
+                let execution = self.vm.execute_script(&self.storage, &tx, gas_limit);
+
+                match execution {
+                    Ok(ref execution) if execution.effects.is_some() => {
+                        print_transfers(&self.ledger.lock().unwrap().transfers);
+                    }
+                    _ => *self.ledger.lock().unwrap() = snapshot,
+                }
+                let execution = execution?;
+
+                self.finish(&execution, expect_status.as_deref(), *expect_abort_code)
+            }
+            Step::AssertResource {
+                address,
+                tag,
+                exists,
+                fields,
+                ..
+            } => {
+                let address = parse_address(address)?;
+                let tag = parse_struct_tag(tag)?;
+                let resource = self
+                    .storage
+                    .get_resource(&address, &tag)
+                    .map_err(|e| Error::msg(format!("{e:?}")))?;
+
+                match (resource, exists.unwrap_or(true)) {
+                    (None, false) => Ok(()),
+                    (Some(_), false) => Err(Error::msg("the resource exists")),
+                    (None, true) => Err(Error::msg("the resource doesn't exist")),
+                    (Some(blob), true) => {
+                        let annotator = MoveValueAnnotator::new(&self.storage);
+                        let resource = annotator.view_resource(&tag, &blob)?;
+
+                        for (field, expected) in fields {
+                            let value = resource
+                                .value
+                                .iter()
+                                .find(|(name, _)| name.as_str() == field)
+                                .map(|(_, value)| value.to_string())
+                                .ok_or(Error::msg(format!("the field '{field}' doesn't exist")))?;
+                            if value != *expected {
+                                return Err(Error::msg(format!(
+                                    "the field '{field}' is {value}, expected {expected}"
+                                )));
+                            }
+                        }
+
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resets the ledger's cheques to the cash limit for every signer of the script.
+    fn issue_cheques(&self, tx: &ScriptTransaction, cash_limit: u128) -> Result<()> {
+        let mut ledger = self.ledger.lock().unwrap();
+        ledger.cheques.clear();
+        ledger.transfers.clear();
+        for signer in script_signers(tx)? {
+            ledger.cheques.insert(signer, cash_limit);
+        }
+
+        Ok(())
+    }
+
+    /// Applies the changes and checks the expected outcome.
+    fn finish(
+        &mut self,
+        execution: &Execution,
+        expect_status: Option<&str>,
+        expect_abort_code: Option<u64>,
+    ) -> Result<()> {
+        execution.apply(&mut self.storage)?;
+
+        let status = format!("{:?}", execution.vm_status_code);
+        let expected_status = expect_status.unwrap_or("EXECUTED");
+        if status != expected_status {
+            return Err(Error::msg(format!(
+                "expected the status {expected_status}, got:\n{}",
+                execution.estimation().explained(self.ctx)
+            )));
+        }
+
+        if expect_abort_code.is_some() && execution.abort_code != expect_abort_code {
+            return Err(Error::msg(format!(
+                "expected the abort code {}, got:\n{}",
+                expect_abort_code.unwrap_or_default(),
+                execution.estimation().explained(self.ctx)
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the script arguments given in the smove's argument syntax.
+fn script_function_args(args: &[String], type_args: &[String]) -> Result<ScriptFunctionArguments> {
+    let mut cli_args = vec!["scenario".to_string()];
+    if !args.is_empty() {
+        cli_args.push("--args".to_string());
+        cli_args.extend(args.iter().cloned());
+    }
+    if !type_args.is_empty() {
+        cli_args.push("--type-args".to_string());
+        cli_args.extend(type_args.iter().cloned());
+    }
+
+    ScriptFunctionArguments::try_parse_from(cli_args).map_err(Error::msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_steps() {
+        let scenario: ScenarioFile = toml::from_str(
+            r#"
+            [balances]
+            "0xCAFE" = 1000
+
+            [[steps]]
+            action = "publish"
+            path = "build/package/bytecode_modules/math.mv"
+
+            [[steps]]
+            action = "execute"
+            name = "transfer"
+            path = "build/package/bytecode_scripts/transfer.mv"
+            args = ["signer:0xCAFE", "u128:10"]
+            cash_limit = 10
+            expect_status = "ABORTED"
+            expect_abort_code = 1
+
+            [[steps]]
+            action = "assert-resource"
+            address = "0xCAFE"
+            tag = "0xCAFE::math::Counter"
+            fields = { value = "1" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            scenario.balances,
+            BTreeMap::from([("0xCAFE".to_string(), 1000)])
+        );
+        assert_eq!(scenario.steps.len(), 3);
+        assert_eq!(
+            scenario.steps[0].name(),
+            "publish build/package/bytecode_modules/math.mv"
+        );
+        assert!(matches!(
+            scenario.steps[1],
+            Step::Execute {
+                ref args,
+                cash_limit: Some(10),
+                expect_abort_code: Some(1),
+                ..
+            } if args.len() == 2
+        ));
+        assert_eq!(scenario.steps[1].name(), "transfer");
+        assert!(matches!(
+            scenario.steps[2],
+            Step::AssertResource { exists: None, ref fields, .. } if fields["value"] == "1"
+        ));
+    }
+
+    #[test]
+    fn invalid_scenario_steps() {
+        let unknown_action = r#"
+            [[steps]]
+            action = "deploy"
+            path = "module.mv"
+        "#;
+        let unknown_field = r#"
+            [[steps]]
+            action = "publish"
+            path = "module.mv"
+            gas = 10
+        "#;
+
+        assert!(toml::from_str::<ScenarioFile>(unknown_action).is_err());
+        assert!(toml::from_str::<ScenarioFile>(unknown_field).is_err());
+        assert!(toml::from_str::<ScenarioFile>("balances = {}").is_err());
+    }
+
+    #[test]
+    fn script_arguments() {
+        let args = script_function_args(&["u64:7".to_string()], &[]).unwrap();
+        assert_eq!(args.args().unwrap(), vec![bcs::to_bytes(&7u64).unwrap()]);
+        assert!(args.type_args().unwrap().is_empty());
+
+        assert!(script_function_args(&["7".to_string()], &[]).is_err());
+    }
+}
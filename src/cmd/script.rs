@@ -1,3 +1,6 @@
+use super::cash_limit::{CashLimitArgs, CashLimitSuggestion};
+use super::metadata::ArtifactMetadata;
+use super::read_bytes;
 use super::script_args::ScriptFunctionArguments;
 use crate::run_context::RunContext;
 use anyhow::{Error, Result};
@@ -5,7 +8,7 @@ use clap::Parser;
 use move_vm_backend_common::bytecode::verify_script_integrity_and_check_signers;
 use move_vm_backend_common::types::ScriptTransaction;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Create a script transaction.
 #[derive(Parser, Debug)]
@@ -17,28 +20,23 @@ pub struct CreateTransaction {
     /// Arguments for script functions.
     #[clap(flatten)]
     script_function_args: ScriptFunctionArguments,
+
+    /// Cash limit for the `execute` extrinsic.
+    #[clap(flatten)]
+    cash_limit_args: CashLimitArgs,
+
+    #[clap(
+        long,
+        help = "Store the cash limit in the transaction's metadata file (e.g. `script.mvt.json`), used by `smove node submit`"
+    )]
+    write_metadata: bool,
 }
 
 impl CreateTransaction {
     /// Executes the command.
     pub fn execute(&mut self, ctx: &RunContext) -> Result<()> {
         let compiled_script = &self.compiled_script_path;
-        let script_bc = fs::read(compiled_script)
-            .map_err(|e| Error::msg(format!("Can't read '{}':\n{e}", compiled_script.display())))?;
-
-        // Check the script bytecode and verify the parameter rules.
-        // This is checked in the Substrate layer again for the safety reasons.
-        let _signer_count = verify_script_integrity_and_check_signers(&script_bc)
-            .map_err(|e| Error::msg(format!("Script parameters verification failure {e:?}")))?;
-
-        let type_args = self.script_function_args.type_args()?;
-        let args = self.script_function_args.args()?;
-
-        let tx = ScriptTransaction {
-            bytecode: script_bc,
-            args,
-            type_args,
-        };
+        let tx = script_transaction(compiled_script, &self.script_function_args)?;
 
         // Path to the output file.
         let tx_name = compiled_script.file_name().unwrap(); // this can't fail in case `fs::read` succeeds above.
@@ -53,6 +51,87 @@ impl CreateTransaction {
             output_file_path.canonicalize()?.display()
         );
 
+        let cash_limit = self.cash_limit_args.cash_limit(&tx)?;
+        if !self.write_metadata {
+            return Ok(());
+        }
+
+        let Some(cash_limit) = cash_limit else {
+            return Err(Error::msg(
+                "The cash limit is unknown and isn't written to the metadata file: set it explicitly with --cash-limit",
+            ));
+        };
+
+        let mut metadata = ArtifactMetadata::load(&output_file_path)?;
+        metadata.cash_limit = Some(cash_limit);
+        let metadata_path = metadata.store(&output_file_path)?;
+        println!(
+            "Cash limit {cash_limit} is written to:\n{}",
+            metadata_path.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Creates a script transaction from the compiled script and its arguments.
+pub(crate) fn script_transaction(
+    compiled_script: &Path,
+    script_function_args: &ScriptFunctionArguments,
+) -> Result<ScriptTransaction> {
+    let script_bc = fs::read(compiled_script)
+        .map_err(|e| Error::msg(format!("Can't read '{}':\n{e}", compiled_script.display())))?;
+
+    // Check the script bytecode and verify the parameter rules.
+    // This is checked in the Substrate layer again for the safety reasons.
+    let _signer_count = verify_script_integrity_and_check_signers(&script_bc)
+        .map_err(|e| Error::msg(format!("Script parameters verification failure {e:?}")))?;
+
+    let type_args = script_function_args.type_args()?;
+    let args = script_function_args.args()?;
+
+    Ok(ScriptTransaction {
+        bytecode: script_bc,
+        args,
+        type_args,
+    })
+}
+
+/// Inspect a script transaction.
+#[derive(Parser, Debug)]
+#[clap(about = "smove inspect-transaction")]
+pub struct InspectTransaction {
+    #[clap(
+        short,
+        long,
+        help = "Path to the script transaction (compiled by the smove create-transaction)"
+    )]
+    script_transaction_path: PathBuf,
+}
+
+impl InspectTransaction {
+    /// Executes the command.
+    pub fn execute(&self) -> Result<()> {
+        let tx: ScriptTransaction = bcs::from_bytes(&read_bytes(&self.script_transaction_path)?)
+            .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+
+        println!("Script bytecode size: {} bytes", tx.bytecode.len());
+        println!("Type arguments: {:?}", tx.type_args);
+        println!("Arguments: {}", tx.args.len());
+        for arg in tx.args.iter() {
+            println!("    0x{}", hex::encode(arg));
+        }
+
+        println!("{}", CashLimitSuggestion::new(&tx)?);
+
+        let metadata = ArtifactMetadata::load(&self.script_transaction_path)?;
+        if let Some(cash_limit) = metadata.cash_limit {
+            println!("Stored cash_limit: {cash_limit}");
+        }
+        if let Some(gas_limit) = metadata.gas_limit {
+            println!("Stored gas_limit: {gas_limit}");
+        }
+
         Ok(())
     }
 }
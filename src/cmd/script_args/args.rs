@@ -3,8 +3,10 @@ use clap::Parser;
 use move_core_types::account_address::AccountAddress;
 use move_core_types::u256::U256;
 use serde::{Serialize, Serializer};
+use serde_json::Value;
 use std::fmt;
 use std::str::FromStr;
+use subxt::utils::AccountId32;
 
 #[derive(Clone, Debug, Parser)]
 pub(super) struct ArgWithTypeVec {
@@ -47,27 +49,14 @@ impl FromStr for ArgWithType {
 
         // These unwraps can't fail.
         let ty = FunctionArgType::from_str(parts.first().unwrap())?;
-        let mut arg = String::from(*parts.last().unwrap());
-        // May need to surround with quotes if not an array, so arg can be parsed into JSON.
-        if !arg.starts_with('[') {
-            if let FunctionArgType::Address
-            | FunctionArgType::Signer
-            | FunctionArgType::Hex
-            | FunctionArgType::String
-            | FunctionArgType::Raw = ty
-            {
-                arg = format!("\"{arg}\"");
-            }
-        }
-
-        let json = serde_json::from_str::<serde_json::Value>(arg.as_str()).map_err(Error::msg)?;
+        let json = ty.arg_to_json(parts.last().unwrap())?;
         ty.parse_arg_json(&json)
     }
 }
 
 /// Type of the function argument.
 #[derive(Clone, Debug, PartialEq, Eq)]
-enum FunctionArgType {
+pub(crate) enum FunctionArgType {
     Signer,
     Address,
     Bool,
@@ -101,6 +90,40 @@ impl fmt::Display for FunctionArgType {
     }
 }
 
+/// Converts the address into the SS58 format expected by the pallet-move RPC methods.
+pub(crate) fn ss58_address(address: &AccountAddress) -> String {
+    AccountId32::from(address.into_bytes()).to_string()
+}
+
+/// Surrounds the unquoted numbers in the JSON array with quotes.
+fn quote_numbers(json: &str) -> String {
+    let mut quoted = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut in_number = false;
+    for c in json.chars() {
+        if !in_string && c.is_ascii_alphanumeric() {
+            if !in_number {
+                quoted.push('"');
+                in_number = true;
+            }
+        } else {
+            if in_number {
+                quoted.push('"');
+                in_number = false;
+            }
+            if c == '"' {
+                in_string = !in_string;
+            }
+        }
+        quoted.push(c);
+    }
+    if in_number {
+        quoted.push('"');
+    }
+
+    quoted
+}
+
 /// Parse the address which can have multiple formats.
 ///
 /// - Move address format
@@ -109,7 +132,7 @@ impl fmt::Display for FunctionArgType {
 ///
 /// - SS58 address format
 ///   - e.g. 5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694t
-fn parse_address(addr: &str) -> Result<AccountAddress> {
+pub(crate) fn parse_address(addr: &str) -> Result<AccountAddress> {
     if let Ok(addr) = move_vm_support::ss58_address::ss58_to_move_address(addr) {
         // TODO: distant future - if an error is in ss58 address, the user won't get any ss58-related error
         Ok(addr)
@@ -119,6 +142,97 @@ fn parse_address(addr: &str) -> Result<AccountAddress> {
 }
 
 impl FunctionArgType {
+    /// Parse the argument string into JSON, so vectors can be given in JSON array syntax.
+    fn arg_to_json(&self, arg: &str) -> Result<Value> {
+        let mut arg = String::from(arg);
+        // May need to surround with quotes if not an array, so arg can be parsed into JSON.
+        if !arg.starts_with('[') {
+            if let FunctionArgType::Address
+            | FunctionArgType::Signer
+            | FunctionArgType::Hex
+            | FunctionArgType::String
+            | FunctionArgType::Raw = self
+            {
+                arg = format!("\"{arg}\"");
+            }
+        } else if let FunctionArgType::U128 | FunctionArgType::U256 = self {
+            // JSON numbers beyond u64 become floats, so the vector elements are kept as strings.
+            arg = quote_numbers(&arg);
+        }
+
+        serde_json::from_str::<Value>(arg.as_str()).map_err(Error::msg)
+    }
+
+    /// Parse the argument into the JSON value expected by the node's RPC methods.
+    ///
+    /// Addresses are converted into SS58, bytes into arrays of numbers, and the integers not fitting
+    /// into `u64` into `0x` prefixed hex strings, as Substrate's `NumberOrHex` expects.
+    pub(crate) fn parse_rpc_arg(&self, arg: &str) -> Result<Value> {
+        // Standalone integers are parsed directly, as JSON numbers lose the precision of u128s.
+        if !arg.starts_with('[') {
+            return self.parse_rpc_arg_str(arg);
+        }
+
+        self.rpc_arg_json(&self.arg_to_json(arg)?)
+    }
+
+    /// Recursively convert argument JSON into the RPC representation.
+    fn rpc_arg_json(&self, arg: &Value) -> Result<Value> {
+        match arg {
+            Value::Bool(value) => self.parse_rpc_arg_str(value.to_string().as_str()),
+            Value::Number(value) => self.parse_rpc_arg_str(value.to_string().as_str()),
+            Value::String(value) => self.parse_rpc_arg_str(value.as_str()),
+            Value::Array(values) => values
+                .iter()
+                .map(|value| self.rpc_arg_json(value))
+                .collect::<Result<_>>()
+                .map(Value::Array),
+            Value::Null => Err(Error::msg("Null argument".to_string())),
+            Value::Object(_) => Err(Error::msg("JSON object argument".to_string())),
+        }
+    }
+
+    /// Parse a standalone argument (not a vector) from string slice into the RPC representation.
+    fn parse_rpc_arg_str(&self, arg: &str) -> Result<Value> {
+        let bytes = |bytes: &[u8]| Value::Array(bytes.iter().copied().map(Value::from).collect());
+        // Big-endian bytes of the integer, without the leading zeros.
+        let integer = |be_bytes: &[u8]| {
+            let start = be_bytes.iter().position(|byte| *byte != 0);
+            let significant = start.map_or(&[][..], |start| &be_bytes[start..]);
+            if significant.len() <= 8 {
+                let mut value = [0u8; 8];
+                value[8 - significant.len()..].copy_from_slice(significant);
+                return Value::from(u64::from_be_bytes(value));
+            }
+
+            let hex = hex::encode(significant);
+            Value::String(format!("0x{}", hex.trim_start_matches('0')))
+        };
+
+        Ok(match self {
+            FunctionArgType::Signer | FunctionArgType::Address => {
+                Value::String(ss58_address(&parse_address(arg)?))
+            }
+            FunctionArgType::Bool => Value::Bool(bool::from_str(arg).map_err(Error::msg)?),
+            FunctionArgType::Hex | FunctionArgType::Raw => {
+                bytes(HexEncodedBytes::from_str(arg)?.inner())
+            }
+            FunctionArgType::String => Value::String(arg.to_string()),
+            FunctionArgType::U8 => Value::from(u8::from_str(arg).map_err(Error::msg)?),
+            FunctionArgType::U16 => Value::from(u16::from_str(arg).map_err(Error::msg)?),
+            FunctionArgType::U32 => Value::from(u32::from_str(arg).map_err(Error::msg)?),
+            FunctionArgType::U64 => Value::from(u64::from_str(arg).map_err(Error::msg)?),
+            FunctionArgType::U128 => {
+                integer(&u128::from_str(arg).map_err(Error::msg)?.to_be_bytes())
+            }
+            FunctionArgType::U256 => {
+                let mut bytes = U256::from_str(arg).map_err(Error::msg)?.to_le_bytes();
+                bytes.reverse();
+                integer(&bytes)
+            }
+        })
+    }
+
     /// Parse a standalone argument (not a vector) from string slice into BCS representation.
     fn parse_arg_str(&self, arg: &str) -> Result<Vec<u8>> {
         match self {
@@ -277,6 +391,12 @@ impl From<[u8; 32]> for HexEncodedBytes {
     }
 }
 
+impl From<Vec<u8>> for HexEncodedBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        HexEncodedBytes(bytes)
+    }
+}
+
 impl fmt::Display for HexEncodedBytes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "0x{}", hex::encode(&self.0))
@@ -293,4 +413,9 @@ impl HexEncodedBytes {
     fn inner(&self) -> &[u8] {
         &self.0
     }
+
+    /// Returns the owned bytes.
+    pub(crate) fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
 }
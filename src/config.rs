@@ -0,0 +1,167 @@
+//! Persistent smove configuration.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Name of the configuration file.
+///
+/// The per-user file is located in the `smove` directory under the user's configuration
+/// directory, while the per-project file is located next to the `Move.toml`.
+const CONFIG_FILE_NAME: &str = "smove.toml";
+
+/// The smove configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SmoveConfig {
+    /// Named network profiles.
+    #[serde(default)]
+    pub networks: BTreeMap<String, NetworkProfile>,
+}
+
+/// Settings for accessing a node.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkProfile {
+    /// Node's URL.
+    pub url: Option<Url>,
+    /// Request timeout in seconds.
+    pub timeout: Option<u64>,
+    /// Number of retries for requests failing on the transport level.
+    pub retries: Option<u32>,
+    /// Additional HTTP headers sent with every request.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Custom CA bundle in the PEM format for verifying the node's certificate.
+    pub ca_bundle: Option<PathBuf>,
+    /// HTTP proxy.
+    pub proxy: Option<Url>,
+    /// Default account ID in the SS58 format.
+    pub account_id: Option<String>,
+}
+
+impl NetworkProfile {
+    /// Overrides the settings with the ones set in the other profile.
+    fn merge(&mut self, other: NetworkProfile) {
+        if other.url.is_some() {
+            self.url = other.url;
+        }
+        if other.timeout.is_some() {
+            self.timeout = other.timeout;
+        }
+        if other.retries.is_some() {
+            self.retries = other.retries;
+        }
+        if other.ca_bundle.is_some() {
+            self.ca_bundle = other.ca_bundle;
+        }
+        if other.proxy.is_some() {
+            self.proxy = other.proxy;
+        }
+        if other.account_id.is_some() {
+            self.account_id = other.account_id;
+        }
+        self.headers.extend(other.headers);
+    }
+}
+
+impl SmoveConfig {
+    /// Loads the per-user configuration and the per-project configuration on top of it.
+    pub fn load(project_root_dir: &Path) -> Result<Self> {
+        let mut config = Self::default();
+
+        let paths = user_config_dir()
+            .map(|dir| dir.join("smove").join(CONFIG_FILE_NAME))
+            .into_iter()
+            .chain([project_root_dir.join(CONFIG_FILE_NAME)]);
+
+        for path in paths.filter(|path| path.exists()) {
+            config.merge(Self::from_file(&path)?);
+        }
+
+        Ok(config)
+    }
+
+    /// Parses the configuration file.
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failure to read filename {}", path.display()))?;
+
+        toml::from_str(&content)
+            .with_context(|| format!("Invalid configuration file {}", path.display()))
+    }
+
+    /// Overrides the settings with the ones set in the other configuration.
+    fn merge(&mut self, other: SmoveConfig) {
+        for (name, profile) in other.networks {
+            self.networks.entry(name).or_default().merge(profile);
+        }
+    }
+
+    /// Get a network profile by its name.
+    pub fn network(&self, name: &str) -> Result<&NetworkProfile> {
+        self.networks.get(name).with_context(|| {
+            format!("Network '{name}' is not defined in the {CONFIG_FILE_NAME} configuration")
+        })
+    }
+}
+
+/// Returns the user's configuration directory.
+fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_config_overrides_user_config() {
+        let mut config: SmoveConfig = toml::from_str(
+            r#"
+            [networks.dev]
+            url = "ws://localhost:9944"
+            timeout = 10
+            headers = { Authorization = "user" }
+
+            [networks.test]
+            url = "wss://test.example.com"
+            "#,
+        )
+        .unwrap();
+        let project: SmoveConfig = toml::from_str(
+            r#"
+            [networks.dev]
+            retries = 3
+            headers = { Authorization = "project", X-Api-Key = "key" }
+
+            [networks.local]
+            url = "http://localhost:9933"
+            "#,
+        )
+        .unwrap();
+
+        config.merge(project);
+
+        let dev = config.network("dev").unwrap();
+        assert_eq!(dev.url.as_ref().unwrap().as_str(), "ws://localhost:9944/");
+        assert_eq!(dev.timeout, Some(10));
+        assert_eq!(dev.retries, Some(3));
+        assert_eq!(dev.headers["Authorization"], "project");
+        assert_eq!(dev.headers["X-Api-Key"], "key");
+        assert!(config.network("test").is_ok());
+        assert!(config.network("local").is_ok());
+        assert!(config.network("main").is_err());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(toml::from_str::<SmoveConfig>("[networks.dev]\nadress = \"x\"").is_err());
+        assert!(toml::from_str::<SmoveConfig>("[network.dev]").is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Human readable explanations for the MoveVM status codes and abort codes.
+
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use move_binary_format::file_format::CompiledScript;
+use move_core_types::errmap::{ErrorDescription, ErrorMapping};
+use move_core_types::language_storage::ModuleId;
+use move_core_types::vm_status::{StatusCode, StatusType};
+use move_vm_backend_common::types::ScriptTransaction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Number of bits used by the error category within the abort code.
+///
+/// Abort codes created with the `std::error` module are composed as `category | (reason << 8)`.
+const CATEGORY_BITS: u64 = 8;
+
+/// Returns the description of the status code.
+pub(crate) fn status_description(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::EXECUTED => "The execution was successful",
+        StatusCode::ABORTED => "The execution was aborted by the Move code",
+        StatusCode::OUT_OF_GAS => "The gas limit was exceeded during the execution",
+        StatusCode::ARITHMETIC_ERROR => "An arithmetic overflow, underflow or division by zero",
+        StatusCode::VECTOR_OPERATION_ERROR => {
+            "An invalid vector operation (e.g. index out of bounds)"
+        }
+        StatusCode::EXECUTION_STACK_OVERFLOW => "The execution stack overflowed",
+        StatusCode::CALL_STACK_OVERFLOW => "The call stack overflowed",
+        StatusCode::MISSING_DATA => "A resource or module was not found in the storage",
+        StatusCode::RESOURCE_ALREADY_EXISTS => "The resource already exists under the address",
+        StatusCode::RESOURCE_DOES_NOT_EXIST => "The resource does not exist under the address",
+        StatusCode::LINKER_ERROR => "A dependency of the module or the script is not published",
+        StatusCode::DUPLICATE_MODULE_NAME => "A module with the same name is already published",
+        StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE => {
+            "The module update is not backward compatible"
+        }
+        StatusCode::MODULE_ADDRESS_DOES_NOT_MATCH_SENDER => {
+            "The module address doesn't match the account publishing it"
+        }
+        StatusCode::NUMBER_OF_SIGNER_ARGUMENTS_MISMATCH => {
+            "The number of signers doesn't match the script's signer parameters"
+        }
+        StatusCode::NUMBER_OF_ARGUMENTS_MISMATCH => {
+            "The number of arguments doesn't match the script parameters"
+        }
+        StatusCode::TYPE_MISMATCH => "An argument type doesn't match the parameter type",
+        StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT => "An argument couldn't be deserialized",
+        StatusCode::NUMBER_OF_TYPE_ARGUMENTS_MISMATCH => {
+            "The number of type arguments doesn't match the type parameters"
+        }
+        StatusCode::UNKNOWN_VERSION => "The bytecode version is not supported by the VM",
+        StatusCode::CODE_DESERIALIZATION_ERROR => "The bytecode couldn't be deserialized",
+        StatusCode::INSUFFICIENT_BALANCE => "The account has insufficient balance",
+        _ => match status.status_type() {
+            StatusType::Validation => "The transaction failed the validation",
+            StatusType::Verification => "The bytecode failed the verification",
+            StatusType::InvariantViolation => "An invariant of the VM was violated",
+            StatusType::Deserialization => "The input couldn't be deserialized",
+            StatusType::Execution => "The execution failed",
+            StatusType::Unknown => "Unknown status",
+        },
+    }
+}
+
+/// Explains the abort code using the error maps.
+///
+/// The candidate modules are the modules which could have aborted, e.g. the abort location of a
+/// local execution or the modules a script calls. Without the candidates, all modules defining
+/// the abort reason are listed.
+pub(crate) fn explain_abort_code(
+    code: u64,
+    candidates: Option<&BTreeSet<ModuleId>>,
+    error_maps: &[&ErrorMapping],
+) -> String {
+    let category = code & ((1 << CATEGORY_BITS) - 1);
+    let reason = code >> CATEGORY_BITS;
+
+    let mut explanation = format!("abort code: {code} (category: {category}, reason: {reason})");
+
+    let category_description = error_maps
+        .iter()
+        .find_map(|error_map| error_map.error_categories.get(&category));
+    if let Some(description) = category_description {
+        let _ = write!(
+            explanation,
+            "\n\tcategory {}",
+            format_description(description)
+        );
+    }
+
+    // A single candidate is the abort location, not a guess.
+    let prefix = match candidates {
+        Some(candidates) if candidates.len() == 1 => "in",
+        _ => "possibly",
+    };
+
+    for error_map in error_maps {
+        let module_error_maps = error_map
+            .module_error_maps
+            .iter()
+            .filter(|(module_id, _)| candidates.map_or(true, |c| c.contains(module_id)));
+        for (module_id, errors) in module_error_maps {
+            // Abort codes without a category are module specific codes.
+            let description = errors.get(&reason).or_else(|| errors.get(&code));
+            if let Some(description) = description {
+                let _ = write!(
+                    explanation,
+                    "\n\t{prefix} {}::{}",
+                    module_id.short_str_lossless(),
+                    format_description(description)
+                );
+            }
+        }
+    }
+
+    explanation
+}
+
+/// Returns the modules the script transaction calls, directly or through the called modules.
+///
+/// The dependencies are followed only through the modules of the package and its dependencies.
+pub(crate) fn script_callees(ctx: &RunContext, script_tx: &[u8]) -> Result<BTreeSet<ModuleId>> {
+    let tx: ScriptTransaction = bcs::from_bytes(script_tx)
+        .map_err(|e| Error::msg(format!("Invalid script transaction: {e}")))?;
+    let script = CompiledScript::deserialize(&tx.bytecode)
+        .map_err(|e| Error::msg(format!("Invalid script: {e:?}")))?;
+
+    let modules = ctx
+        .get_compiled_modules()?
+        .into_iter()
+        .map(|module| (module.self_id(), module))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut callees = BTreeSet::new();
+    let mut pending = script.immediate_dependencies();
+    while let Some(module_id) = pending.pop() {
+        if !callees.insert(module_id.clone()) {
+            continue;
+        }
+        if let Some(module) = modules.get(&module_id) {
+            pending.extend(module.immediate_dependencies());
+        }
+    }
+
+    Ok(callees)
+}
+
+/// Formats the error description.
+fn format_description(description: &ErrorDescription) -> String {
+    format!(
+        "{}: {}",
+        description.code_name, description.code_description
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::account_address::AccountAddress;
+    use move_core_types::identifier::Identifier;
+
+    fn description(code_name: &str) -> ErrorDescription {
+        ErrorDescription {
+            code_name: code_name.to_string(),
+            code_description: format!("{code_name} description"),
+        }
+    }
+
+    fn module_id(name: &str) -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, Identifier::new(name).unwrap())
+    }
+
+    fn error_map() -> ErrorMapping {
+        ErrorMapping {
+            error_categories: BTreeMap::from([(1, description("INVALID_ARGUMENT"))]),
+            module_error_maps: BTreeMap::from([
+                (
+                    module_id("coin"),
+                    BTreeMap::from([(2, description("ECOIN"))]),
+                ),
+                (
+                    module_id("vault"),
+                    BTreeMap::from([(2, description("EVAULT"))]),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn abort_code_is_split_into_category_and_reason() {
+        let explanation = explain_abort_code(0x201, None, &[]);
+
+        assert_eq!(explanation, "abort code: 513 (category: 1, reason: 2)");
+    }
+
+    #[test]
+    fn abort_code_without_candidates_lists_all_modules() {
+        let error_map = error_map();
+        let explanation = explain_abort_code(0x201, None, &[&error_map]);
+
+        assert!(explanation.contains("category INVALID_ARGUMENT: INVALID_ARGUMENT description"));
+        assert!(explanation.contains("possibly 0x1::coin::ECOIN"));
+        assert!(explanation.contains("possibly 0x1::vault::EVAULT"));
+    }
+
+    #[test]
+    fn abort_code_with_single_candidate_names_the_location() {
+        let error_map = error_map();
+        let candidates = BTreeSet::from([module_id("vault")]);
+        let explanation = explain_abort_code(0x201, Some(&candidates), &[&error_map]);
+
+        assert!(explanation.contains("in 0x1::vault::EVAULT"));
+        assert!(!explanation.contains("coin"));
+    }
+
+    #[test]
+    fn abort_code_with_candidates_skips_other_modules() {
+        let error_map = error_map();
+        let candidates = BTreeSet::from([module_id("coin"), module_id("signer")]);
+        let explanation = explain_abort_code(0x201, Some(&candidates), &[&error_map]);
+
+        assert!(explanation.contains("possibly 0x1::coin::ECOIN"));
+        assert!(!explanation.contains("vault"));
+    }
+
+    #[test]
+    fn module_specific_abort_code_is_matched_without_category() {
+        let error_map = error_map();
+        let explanation = explain_abort_code(2, None, &[&error_map]);
+
+        assert!(explanation.contains("possibly 0x1::coin::ECOIN"));
+    }
+}
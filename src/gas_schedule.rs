@@ -0,0 +1,233 @@
+//! Gas schedules for the local executions stored in JSON or TOML files.
+
+use anyhow::{Context, Error, Result};
+use move_core_types::gas_algebra::GasQuantity;
+use move_stdlib::natives::GasParameters;
+use move_vm_backend_common::gas_schedule::{INSTRUCTION_COST_TABLE, NATIVE_COST_PARAMS};
+use move_vm_test_utils::gas_schedule::CostTable;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Extension of the TOML files, other files are handled as JSON.
+const TOML_EXTENSION: &str = "toml";
+
+/// Gas schedule with the instruction costs and the native function parameters.
+#[derive(Serialize, Deserialize)]
+pub struct GasSchedule {
+    /// Instruction costs.
+    #[serde(flatten)]
+    pub cost_table: CostTable,
+    /// Native function parameters by their names, e.g. `hash.sha2_256.base`.
+    ///
+    /// The missing parameters keep the built-in values, so the files with only the instruction
+    /// costs remain valid.
+    #[serde(default)]
+    pub natives: BTreeMap<String, u64>,
+}
+
+impl GasSchedule {
+    /// Returns the built-in gas schedule, which pallet-move uses.
+    pub fn built_in() -> Self {
+        Self {
+            cost_table: INSTRUCTION_COST_TABLE.clone(),
+            natives: native_params_map(&NATIVE_COST_PARAMS),
+        }
+    }
+
+    /// Returns the built-in native function parameters overridden by the schedule's ones.
+    pub fn native_params(&self) -> Result<GasParameters> {
+        let mut params = NATIVE_COST_PARAMS.clone();
+        for (name, value) in self.natives.iter() {
+            set_native_param(&mut params, name, *value)?;
+        }
+
+        Ok(params)
+    }
+}
+
+/// Defines the conversions between the native function parameters and their names in the files.
+macro_rules! native_params {
+    ($($name:literal => $($field:ident).+,)*) => {
+        /// Returns the native function parameters by their names.
+        fn native_params_map(params: &GasParameters) -> BTreeMap<String, u64> {
+            BTreeMap::from([$(($name.to_string(), u64::from(params.$($field).+)),)*])
+        }
+
+        /// Sets the native function parameter with the name.
+        fn set_native_param(params: &mut GasParameters, name: &str, value: u64) -> Result<()> {
+            match name {
+                $($name => params.$($field).+ = GasQuantity::new(value),)*
+                _ => {
+                    return Err(Error::msg(format!(
+                        "Unknown native function parameter '{name}' in the gas schedule"
+                    )))
+                }
+            }
+
+            Ok(())
+        }
+    };
+}
+
+native_params! {
+    "bcs.to_bytes.per_byte_serialized" => bcs.to_bytes.per_byte_serialized,
+    "bcs.to_bytes.legacy_min_output_size" => bcs.to_bytes.legacy_min_output_size,
+    "bcs.to_bytes.failure" => bcs.to_bytes.failure,
+    "hash.sha2_256.base" => hash.sha2_256.base,
+    "hash.sha2_256.per_byte" => hash.sha2_256.per_byte,
+    "hash.sha2_256.legacy_min_input_len" => hash.sha2_256.legacy_min_input_len,
+    "hash.sha3_256.base" => hash.sha3_256.base,
+    "hash.sha3_256.per_byte" => hash.sha3_256.per_byte,
+    "hash.sha3_256.legacy_min_input_len" => hash.sha3_256.legacy_min_input_len,
+    "signer.borrow_address.base" => signer.borrow_address.base,
+    "string.check_utf8.base" => string.check_utf8.base,
+    "string.check_utf8.per_byte" => string.check_utf8.per_byte,
+    "string.is_char_boundary.base" => string.is_char_boundary.base,
+    "string.sub_string.base" => string.sub_string.base,
+    "string.sub_string.per_byte" => string.sub_string.per_byte,
+    "string.index_of.base" => string.index_of.base,
+    "string.index_of.per_byte_pattern" => string.index_of.per_byte_pattern,
+    "string.index_of.per_byte_searched" => string.index_of.per_byte_searched,
+    "type_name.get.base" => type_name.get.base,
+    "type_name.get.per_byte" => type_name.get.per_byte,
+    "vector.empty.base" => vector.empty.base,
+    "vector.length.base" => vector.length.base,
+    "vector.push_back.base" => vector.push_back.base,
+    "vector.push_back.legacy_per_abstract_memory_unit" => vector.push_back.legacy_per_abstract_memory_unit,
+    "vector.borrow.base" => vector.borrow.base,
+    "vector.pop_back.base" => vector.pop_back.base,
+    "vector.destroy_empty.base" => vector.destroy_empty.base,
+    "vector.swap.base" => vector.swap.base,
+}
+
+/// Loads the gas schedule from the JSON or TOML file, the format is chosen by the extension.
+pub fn load(path: &Path) -> Result<GasSchedule> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failure to read filename {}", path.display()))?;
+
+    let schedule = if is_toml(path) {
+        toml::from_str(&content).map_err(Error::msg)
+    } else {
+        serde_json::from_str(&content).map_err(Error::msg)
+    }
+    .with_context(|| format!("Invalid gas schedule {}", path.display()))?;
+
+    validate(&schedule)?;
+
+    Ok(schedule)
+}
+
+/// Serializes the gas schedule in the format chosen by the file's extension, JSON by default.
+pub fn to_string(schedule: &GasSchedule, path: Option<&Path>) -> Result<String> {
+    match path {
+        Some(path) if is_toml(path) => Ok(toml::to_string_pretty(schedule)?),
+        _ => Ok(serde_json::to_string_pretty(schedule)?),
+    }
+}
+
+/// Checks the gas schedule has a cost for every instruction and only known native parameters.
+pub fn validate(schedule: &GasSchedule) -> Result<()> {
+    let expected = INSTRUCTION_COST_TABLE.instruction_table.len();
+    let found = schedule.cost_table.instruction_table.len();
+    if found != expected {
+        return Err(Error::msg(format!(
+            "The gas schedule has {found} instruction costs, but the MoveVM expects {expected}"
+        )));
+    }
+
+    schedule.native_params()?;
+
+    Ok(())
+}
+
+/// Checks if the file is a TOML file.
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == TOML_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the content into a temporary file with the extension and loads it.
+    fn load_content(name: &str, extension: &str, content: &str) -> Result<GasSchedule> {
+        let path = std::env::temp_dir()
+            .join(format!("smove-{name}-{}", std::process::id()))
+            .with_extension(extension);
+        fs::write(&path, content).unwrap();
+        let schedule = load(&path);
+        fs::remove_file(&path).unwrap();
+
+        schedule
+    }
+
+    #[test]
+    fn export_and_load_round_trip() {
+        let built_in = GasSchedule::built_in();
+
+        for extension in ["json", "toml"] {
+            let path = Path::new("gas-schedule").with_extension(extension);
+            let exported = to_string(&built_in, Some(&path)).unwrap();
+            let loaded = load_content("round-trip", extension, &exported).unwrap();
+
+            assert_eq!(to_string(&loaded, Some(&path)).unwrap(), exported);
+            assert_eq!(loaded.natives, built_in.natives);
+        }
+    }
+
+    #[test]
+    fn native_param_names_map_to_their_own_fields() {
+        let built_in = native_params_map(&NATIVE_COST_PARAMS);
+
+        for (name, value) in built_in.iter() {
+            let mut params = NATIVE_COST_PARAMS.clone();
+            set_native_param(&mut params, name, value + 1).unwrap();
+
+            let mut expected = built_in.clone();
+            expected.insert(name.clone(), value + 1);
+            assert_eq!(native_params_map(&params), expected, "{name}");
+        }
+
+        let mut params = NATIVE_COST_PARAMS.clone();
+        set_native_param(&mut params, "hash.sha2_256.per_byte", 7).unwrap();
+        assert_eq!(u64::from(params.hash.sha2_256.per_byte), 7);
+    }
+
+    #[test]
+    fn missing_native_params_keep_the_built_in_values() {
+        let schedule = GasSchedule {
+            cost_table: INSTRUCTION_COST_TABLE.clone(),
+            natives: BTreeMap::from([("vector.length.base".to_string(), 42)]),
+        };
+
+        let params = native_params_map(&schedule.native_params().unwrap());
+        let mut expected = native_params_map(&NATIVE_COST_PARAMS);
+        expected.insert("vector.length.base".to_string(), 42);
+        assert_eq!(params, expected);
+    }
+
+    #[test]
+    fn unknown_native_params_are_rejected() {
+        let schedule = GasSchedule {
+            cost_table: INSTRUCTION_COST_TABLE.clone(),
+            natives: BTreeMap::from([("hash.md5.base".to_string(), 1)]),
+        };
+
+        assert!(schedule.native_params().is_err());
+        assert!(validate(&schedule).is_err());
+    }
+
+    #[test]
+    fn missing_instruction_costs_are_rejected() {
+        let mut schedule = GasSchedule::built_in();
+        schedule.cost_table.instruction_table.pop();
+        let path = Path::new("gas-schedule.json");
+        let content = to_string(&schedule, Some(path)).unwrap();
+
+        let error = load_content("missing-costs", "json", &content).unwrap_err();
+        assert!(error.to_string().contains("instruction costs"));
+        assert!(load_content("no-table", "json", "{}").is_err());
+    }
+}
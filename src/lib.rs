@@ -1,10 +1,16 @@
 use crate::run_context::RunContext;
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use move_cli::Move;
 use std::path::PathBuf;
 
 mod cmd;
+mod config;
+mod explain;
+mod gas_schedule;
+mod local_vm;
+mod natives;
+mod profiler;
 mod run_context;
 mod run_move_cli;
 
@@ -16,6 +22,14 @@ struct SmoveArgs {
     #[clap(flatten)]
     move_args: Move,
 
+    /// Gas schedule replacing the built-in one.
+    #[clap(
+        long,
+        global = true,
+        help = "Gas schedule file (JSON or TOML) for the local executions, tests and the sandbox"
+    )]
+    gas_schedule: Option<PathBuf>,
+
     /// Commands.
     #[clap(subcommand)]
     cmd: SmoveCommand,
@@ -49,31 +63,129 @@ enum SmoveCommand {
         cmd: cmd::script::CreateTransaction,
     },
 
+    /// Local pallet-move simulator.
+    #[clap(about = "Local pallet-move simulator with the state stored in a directory")]
+    Devnet {
+        #[clap(flatten)]
+        cmd: cmd::devnet::Devnet,
+    },
+
+    /// Estimate gas for executing a script transaction.
+    #[clap(about = "Estimate gas for executing a script transaction on the node or locally")]
+    Estimate {
+        #[clap(flatten)]
+        cmd: cmd::estimate::Estimate,
+    },
+
+    /// Export SCALE-encoded call data for pallet-move calls.
+    #[clap(about = "Export SCALE-encoded call data for pallet-move calls")]
+    ExportCall {
+        #[clap(flatten)]
+        cmd: cmd::export_call::ExportCall,
+    },
+
+    /// Fork the on-chain state into the local devnet.
+    #[clap(about = "Fork the modules and resources of accounts into the local devnet storage")]
+    Fork {
+        #[clap(flatten)]
+        cmd: cmd::fork::Fork,
+    },
+
+    /// Gas schedule commands.
+    #[clap(subcommand, about = "Export the gas schedule")]
+    GasSchedule(cmd::gas_schedule::GasSchedule),
+
+    /// Store or check the gas snapshot.
+    #[clap(about = "Store or check the estimated gas of all script transactions and bundles")]
+    GasSnapshot {
+        #[clap(flatten)]
+        cmd: cmd::gas_snapshot::GasSnapshot,
+    },
+
+    /// Inspect a script transaction.
+    #[clap(about = "Show the script transaction's content and the suggested cash limit")]
+    InspectTransaction {
+        #[clap(flatten)]
+        cmd: cmd::script::InspectTransaction,
+    },
+
+    /// Multisig script execution commands.
+    #[clap(subcommand, about = "Multisig script execution commands")]
+    Multisig(cmd::multisig::Multisig),
+
     /// Commands for accessing the node.
     #[clap(about = "Commands for accessing the node")]
     Node {
         #[clap(flatten)]
         cmd: cmd::node::Node,
     },
+
+    /// Profile the gas used per function by a script transaction.
+    #[clap(
+        about = "Profile the gas used per function by a script transaction with the embedded MoveVM"
+    )]
+    Profile {
+        #[clap(flatten)]
+        cmd: cmd::profile::Profile,
+    },
+
+    /// Scenario commands.
+    #[clap(
+        subcommand,
+        about = "Run deployment scenarios with the embedded MoveVM"
+    )]
+    Scenario(cmd::scenario::Scenario),
 }
 
 /// Run the smove CLI.
 pub fn smove_cli(cwd: PathBuf) -> Result<()> {
-    let SmoveArgs { move_args, cmd } = SmoveArgs::parse();
+    let matches = cmd::sandbox::augment_sandbox(SmoveArgs::command()).get_matches();
 
-    let project_root_dir = if let Some(ref project_path) = move_args.package_path {
-        project_path.canonicalize()?
-    } else {
-        cwd
-    };
+    // smove's sandbox subcommands are unknown to the move-cli's sandbox command.
+    if let Some(sandbox) = cmd::sandbox::Sandbox::from_matches(&matches)? {
+        let move_args = Move::from_arg_matches(&matches)?;
+        let gas_schedule = matches
+            .get_raw("gas-schedule")?
+            .and_then(|mut values| values.next())
+            .map(PathBuf::from);
+
+        return sandbox.execute(&run_context(cwd, move_args, gas_schedule)?);
+    }
+
+    let SmoveArgs {
+        move_args,
+        gas_schedule,
+        cmd,
+    } = SmoveArgs::from_arg_matches(&matches)?;
 
-    let ctx = RunContext::new(project_root_dir, move_args)?;
+    let ctx = run_context(cwd, move_args, gas_schedule)?;
 
     match cmd {
         SmoveCommand::MoveCommand(cmd) => run_move_cli::run_command(&ctx, cmd),
         SmoveCommand::Bundle { mut cmd } => cmd.execute(&ctx),
-        SmoveCommand::Node { mut cmd } => cmd.execute(),
+        SmoveCommand::Node { mut cmd } => cmd.execute(&ctx),
         SmoveCommand::CreateTransaction { mut cmd } => cmd.execute(&ctx),
         SmoveCommand::CallHash { cmd } => cmd.execute(),
+        SmoveCommand::Devnet { cmd } => cmd.execute(&ctx),
+        SmoveCommand::Estimate { cmd } => cmd.execute(&ctx),
+        SmoveCommand::ExportCall { cmd } => cmd.execute(),
+        SmoveCommand::Fork { cmd } => cmd.execute(&ctx),
+        SmoveCommand::GasSchedule(cmd) => cmd.execute(),
+        SmoveCommand::GasSnapshot { cmd } => cmd.execute(&ctx),
+        SmoveCommand::InspectTransaction { cmd } => cmd.execute(),
+        SmoveCommand::Multisig(cmd) => cmd.execute(&ctx),
+        SmoveCommand::Profile { cmd } => cmd.execute(&ctx),
+        SmoveCommand::Scenario(cmd) => cmd.execute(&ctx),
     }
 }
+
+/// Creates the run context for the package in the current directory or in the package path.
+fn run_context(cwd: PathBuf, move_args: Move, gas_schedule: Option<PathBuf>) -> Result<RunContext> {
+    let project_root_dir = if let Some(ref project_path) = move_args.package_path {
+        project_path.canonicalize()?
+    } else {
+        cwd
+    };
+
+    RunContext::new(project_root_dir, move_args, gas_schedule.as_deref())
+}
@@ -0,0 +1,279 @@
+//! MoveVM embedded in smove for executing the package's artifacts locally.
+
+use crate::cmd::node::rpc::Estimation;
+use crate::natives::reset_session;
+use crate::run_context::RunContext;
+use anyhow::{Error, Result};
+use move_binary_format::errors::{Location, VMError};
+use move_binary_format::CompiledModule;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::effects::{ChangeSet, Event};
+use move_core_types::language_storage::ModuleId;
+use move_core_types::resolver::MoveResolver;
+use move_core_types::vm_status::StatusCode;
+use move_vm_backend_common::types::{ModuleBundle, ScriptTransaction};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::native_functions::NativeFunctionTable;
+use move_vm_test_utils::gas_schedule::{CostTable, Gas, GasStatus};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::GasMeter;
+use std::fs;
+use std::path::Path;
+
+/// Gas limit for the estimations, high enough for any reasonable execution.
+pub(crate) const ESTIMATION_GAS_LIMIT: u64 = 1_000_000_000_000;
+
+/// Result of a metered execution.
+pub(crate) struct Execution {
+    /// Gas used.
+    pub(crate) gas_used: u64,
+    /// Status code for the MoveVM execution.
+    pub(crate) vm_status_code: StatusCode,
+    /// Abort code in case the execution was aborted.
+    pub(crate) abort_code: Option<u64>,
+    /// Module which aborted the execution, unknown for the script's own aborts.
+    pub(crate) abort_location: Option<ModuleId>,
+    /// Changes and events made by a successful execution.
+    pub(crate) effects: Option<(ChangeSet, Vec<Event>)>,
+}
+
+impl Execution {
+    /// Creates the result from the gas used and the VM output.
+    pub(crate) fn new(gas_used: u64, output: Result<(ChangeSet, Vec<Event>), VMError>) -> Self {
+        match output {
+            Ok(effects) => Self {
+                gas_used,
+                vm_status_code: StatusCode::EXECUTED,
+                abort_code: None,
+                abort_location: None,
+                effects: Some(effects),
+            },
+            Err(e) => Self {
+                gas_used,
+                vm_status_code: e.major_status(),
+                abort_code: e.sub_status(),
+                abort_location: match e.location() {
+                    Location::Module(module_id) => Some(module_id.clone()),
+                    _ => None,
+                },
+                effects: None,
+            },
+        }
+    }
+
+    /// Applies the changes of a successful execution to the storage.
+    pub(crate) fn apply(&self, storage: &mut InMemoryStorage) -> Result<()> {
+        if let Some((ref changes, _)) = self.effects {
+            storage.apply(changes.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Converts the result into the same format the node's gas estimation returns.
+    pub(crate) fn estimation(&self) -> Estimation {
+        Estimation::new(
+            self.gas_used,
+            self.vm_status_code,
+            self.abort_code,
+            self.abort_location.clone(),
+        )
+    }
+}
+
+/// MoveVM using the natives and the cost table from the run context.
+///
+/// Every execution starts a new session of the mocked natives, so it sees the configured values.
+pub(crate) struct LocalVm {
+    /// Virtual machine.
+    vm: MoveVM,
+    /// Cost table for metering.
+    cost_table: CostTable,
+}
+
+impl LocalVm {
+    /// Create a new instance.
+    pub(crate) fn new(ctx: &RunContext) -> Result<Self> {
+        Self::with_natives(ctx, vec![])
+    }
+
+    /// Create a new instance with additional natives, e.g. the balance natives bound to a ledger.
+    ///
+    /// The additional natives replace the context's natives with the same name.
+    pub(crate) fn with_natives(ctx: &RunContext, natives: NativeFunctionTable) -> Result<Self> {
+        let natives = ctx
+            .natives()?
+            .iter()
+            .filter(|(address, module, name, _)| {
+                !natives
+                    .iter()
+                    .any(|(a, m, n, _)| a == address && m == module && n == name)
+            })
+            .cloned()
+            .chain(natives.clone());
+        let vm = MoveVM::new(natives)
+            .map_err(|e| Error::msg(format!("Failed to create the MoveVM: {e:?}")))?;
+
+        Ok(Self {
+            vm,
+            cost_table: ctx.cost_table.clone(),
+        })
+    }
+
+    /// Publishes the modules without verification and metering, like the modules available at
+    /// the chain's genesis.
+    pub(crate) fn publish_genesis_modules(
+        storage: &mut InMemoryStorage,
+        module_paths: &[impl AsRef<Path>],
+    ) -> Result<()> {
+        for path in module_paths {
+            let bytecode = fs::read(path.as_ref())?;
+            let module = CompiledModule::deserialize(&bytecode).map_err(|e| {
+                Error::msg(format!("Invalid module {}: {e:?}", path.as_ref().display()))
+            })?;
+            storage.publish_or_overwrite_module(module.self_id(), bytecode);
+        }
+
+        Ok(())
+    }
+
+    /// Publishes the module, the changes are returned without being applied.
+    ///
+    /// The MoveVM checks the module's address matches the sender, as the pallet requires.
+    pub(crate) fn publish_module(
+        &self,
+        storage: &impl MoveResolver,
+        module: Vec<u8>,
+        sender: AccountAddress,
+        gas_limit: u64,
+    ) -> Result<Execution> {
+        let mut gas_status = self.gas_status(gas_limit);
+        reset_session();
+        let mut session = self.vm.new_session(storage);
+        let output = session
+            .publish_module(module, sender, &mut gas_status)
+            .and_then(|_| session.finish());
+
+        Self::finish(gas_limit, &gas_status, output)
+    }
+
+    /// Publishes the bundle, the changes are returned without being applied.
+    ///
+    /// The MoveVM checks the modules' address matches the sender, as the pallet requires.
+    pub(crate) fn publish_bundle(
+        &self,
+        storage: &impl MoveResolver,
+        bundle: &[u8],
+        sender: AccountAddress,
+        gas_limit: u64,
+    ) -> Result<Execution> {
+        let modules = decode_bundle(bundle)?;
+
+        let mut gas_status = self.gas_status(gas_limit);
+        reset_session();
+        let mut session = self.vm.new_session(storage);
+        let output = session
+            .publish_module_bundle(modules, sender, &mut gas_status)
+            .and_then(|_| session.finish());
+
+        Self::finish(gas_limit, &gas_status, output)
+    }
+
+    /// Executes the script transaction, the changes are returned without being applied.
+    pub(crate) fn execute_script(
+        &self,
+        storage: &impl MoveResolver,
+        tx: &ScriptTransaction,
+        gas_limit: u64,
+    ) -> Result<Execution> {
+        let mut gas_status = self.gas_status(gas_limit);
+        let output = self.execute_script_metered(storage, tx, &mut gas_status);
+
+        Self::finish(gas_limit, &gas_status, output)
+    }
+
+    /// Executes the script transaction metered by the given gas meter, the changes are returned
+    /// without being applied.
+    pub(crate) fn execute_script_metered(
+        &self,
+        storage: &impl MoveResolver,
+        tx: &ScriptTransaction,
+        gas_meter: &mut impl GasMeter,
+    ) -> Result<(ChangeSet, Vec<Event>), VMError> {
+        reset_session();
+        let mut session = self.vm.new_session(storage);
+        session
+            .execute_script(
+                tx.bytecode.as_slice(),
+                tx.type_args.clone(),
+                tx.args.clone(),
+                gas_meter,
+            )
+            .and_then(|_| session.finish())
+    }
+
+    /// Creates the gas meter with the configured cost table.
+    pub(crate) fn gas_status(&self, gas_limit: u64) -> GasStatus {
+        GasStatus::new(&self.cost_table, Gas::new(gas_limit))
+    }
+
+    /// Collects the result of the metered execution.
+    fn finish(
+        gas_limit: u64,
+        gas_status: &GasStatus,
+        output: Result<(ChangeSet, Vec<Event>), VMError>,
+    ) -> Result<Execution> {
+        let gas_used = gas_limit - u64::from(gas_status.remaining_gas());
+
+        Ok(Execution::new(gas_used, output))
+    }
+}
+
+/// Creates the storage with the package's dependencies and bundles published.
+///
+/// Without bundles, the package's modules are published as they are.
+pub(crate) fn package_storage(ctx: &RunContext, vm: &LocalVm) -> Result<InMemoryStorage> {
+    let mut storage = InMemoryStorage::new();
+    LocalVm::publish_genesis_modules(&mut storage, &ctx.get_dependency_modules()?)?;
+
+    let bundles = ctx.get_bundles()?;
+    if bundles.is_empty() {
+        LocalVm::publish_genesis_modules(&mut storage, &ctx.get_bytecode_modules()?)?;
+    }
+
+    for path in bundles {
+        let bundle = fs::read(&path)?;
+        let execution = vm.publish_bundle(
+            &storage,
+            &bundle,
+            bundle_address(&bundle)?,
+            ESTIMATION_GAS_LIMIT,
+        )?;
+        if execution.vm_status_code != StatusCode::EXECUTED {
+            return Err(Error::msg(format!(
+                "Failed to publish the bundle {} with the status code {:?}",
+                path.display(),
+                execution.vm_status_code
+            )));
+        }
+        execution.apply(&mut storage)?;
+    }
+
+    Ok(storage)
+}
+
+/// Decodes the modules of the bundle.
+pub(crate) fn decode_bundle(bundle: &[u8]) -> Result<Vec<Vec<u8>>> {
+    Ok(bcs::from_bytes::<ModuleBundle>(bundle)
+        .map_err(|e| Error::msg(format!("Invalid bundle: {e}")))?
+        .into_inner())
+}
+
+/// Returns the address of the bundle's modules, which is the account publishing the bundle.
+pub(crate) fn bundle_address(bundle: &[u8]) -> Result<AccountAddress> {
+    decode_bundle(bundle)?
+        .first()
+        .and_then(|module| CompiledModule::deserialize(module).ok())
+        .map(|module| *module.self_id().address())
+        .ok_or(Error::msg("The bundle has no valid modules"))
+}
@@ -0,0 +1,492 @@
+//! Substrate-specific native functions emulated locally.
+//!
+//! The mocked natives registered in the run context start every session from the values
+//! configured in the package manifest. The state is kept per thread and reset when a new session
+//! starts, so unit tests running in parallel don't see each other's transfers, while a test (or
+//! a sandbox session) observes the balance changes made by its own transfers.
+
+use crate::cmd::script_args::args::parse_address;
+use anyhow::{Context, Result};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::gas_algebra::InternalGas;
+use move_core_types::identifier::Identifier;
+use move_vm_runtime::native_extensions::NativeContextExtensions;
+use move_vm_runtime::native_functions::{NativeContext, NativeFunction, NativeFunctionTable};
+use move_vm_types::loaded_data::runtime_types::Type;
+use move_vm_types::natives::function::NativeResult;
+use move_vm_types::pop_arg;
+use move_vm_types::values::{Reference, SignerRef, Value};
+use serde::Deserialize;
+use smallvec::smallvec;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Name of the substrate-stdlib module providing the balance natives.
+const BALANCE_MODULE: &str = "balance";
+
+/// Name of the substrate-stdlib module providing the block info natives.
+const BLOCK_MODULE: &str = "block";
+
+/// Table of the package manifest with the mocked values.
+const NATIVES_TABLE: &str = "natives";
+
+/// Cost of reading or setting a value of the mocked state, e.g. a balance or the block number,
+/// in internal gas units.
+const STATE_ACCESS_COST: u64 = 1_000;
+
+/// Cost of a balance transfer in internal gas units, it reads and writes the balances of both
+/// accounts and the sender's cheque.
+const TRANSFER_COST: u64 = 5 * STATE_ACCESS_COST;
+
+thread_local! {
+    /// Mocked state of the session running on the thread, created from the configured values on
+    /// the first use.
+    static SESSION: RefCell<Option<(SharedLedger, SharedBlock)>> = RefCell::new(None);
+}
+
+/// Values returned by the mocked substrate-stdlib natives in `smove test`, the sandbox and the
+/// local executions, configured in the `[natives]` table of the package manifest.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NativesMock {
+    /// Free balance per account (SS58 or Move address).
+    #[serde(default)]
+    balances: BTreeMap<String, u128>,
+    /// Amount each account allows to transfer from its balance (SS58 or Move address).
+    #[serde(default)]
+    cheques: BTreeMap<String, u128>,
+    /// Current block number.
+    #[serde(default)]
+    block_number: u64,
+    /// Timestamp of the current block in milliseconds.
+    #[serde(default)]
+    timestamp: u64,
+}
+
+impl NativesMock {
+    /// Reads the `[natives]` table of the package manifest, the manifest's other tables are
+    /// ignored.
+    pub(crate) fn load(manifest_path: &Path) -> Result<Self> {
+        if !manifest_path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(manifest_path)
+            .with_context(|| format!("Failure to read filename {}", manifest_path.display()))?;
+        let manifest: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("Invalid manifest {}", manifest_path.display()))?;
+
+        match manifest.get(NATIVES_TABLE) {
+            Some(natives) => natives
+                .clone()
+                .try_into()
+                .with_context(|| format!("Invalid [natives] table in {}", manifest_path.display())),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Balance transfer done by a script.
+#[derive(Debug, Clone)]
+pub(crate) struct Transfer {
+    /// Sender.
+    pub(crate) src: AccountAddress,
+    /// Recipient.
+    pub(crate) dst: AccountAddress,
+    /// Transferred amount.
+    pub(crate) amount: u128,
+}
+
+/// Balances of the accounts and the cheques the signers issued for the script execution.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct BalanceLedger {
+    /// Free balance per account.
+    pub(crate) balances: BTreeMap<AccountAddress, u128>,
+    /// Remaining amount each signer allowed the script to transfer (the cash limit).
+    pub(crate) cheques: BTreeMap<AccountAddress, u128>,
+    /// Transfers done by the script.
+    pub(crate) transfers: Vec<Transfer>,
+}
+
+impl BalanceLedger {
+    /// Transfers the amount if the sender's cheque and balance cover it.
+    fn transfer(&mut self, src: AccountAddress, dst: AccountAddress, amount: u128) -> bool {
+        let cheque = self.cheques.get(&src).copied().unwrap_or_default();
+        let balance = self.balances.get(&src).copied().unwrap_or_default();
+        if cheque < amount || balance < amount {
+            return false;
+        }
+
+        self.cheques.insert(src, cheque - amount);
+        self.balances.insert(src, balance - amount);
+        let dst_balance = self.balances.entry(dst).or_default();
+        *dst_balance = dst_balance.saturating_add(amount);
+        self.transfers.push(Transfer { src, dst, amount });
+
+        true
+    }
+}
+
+/// Block info returned by the block natives.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct BlockInfo {
+    /// Block number.
+    pub(crate) number: u64,
+    /// Block timestamp in milliseconds.
+    pub(crate) timestamp: u64,
+}
+
+/// Ledger shared between the natives and their owner.
+pub(crate) type SharedLedger = Arc<Mutex<BalanceLedger>>;
+
+/// Block info shared between the natives and their owner.
+pub(crate) type SharedBlock = Arc<Mutex<BlockInfo>>;
+
+/// Returns the state the natives operate on when they're called.
+type StateSource<T> = Arc<dyn Fn() -> Arc<Mutex<T>> + Send + Sync>;
+
+/// Returns the substrate-stdlib natives with the values mocked in the manifest, including the
+/// test-only natives setting the values.
+///
+/// Every execution runs in a new session, so it starts with the configured values, see
+/// [`reset_session`] and [`reset_session_per_unit_test`].
+pub(crate) fn mocked_natives(mock: &NativesMock) -> Result<NativeFunctionTable> {
+    let parse = |accounts: &BTreeMap<String, u128>| {
+        accounts
+            .iter()
+            .map(|(account, amount)| Ok((parse_address(account)?, *amount)))
+            .collect::<Result<BTreeMap<_, _>>>()
+    };
+
+    let ledger = BalanceLedger {
+        balances: parse(&mock.balances)?,
+        cheques: parse(&mock.cheques)?,
+        ..Default::default()
+    };
+    let block = BlockInfo {
+        number: mock.block_number,
+        timestamp: mock.timestamp,
+    };
+
+    let session = Arc::new(move || {
+        SESSION.with(|session| {
+            session
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    (
+                        Arc::new(Mutex::new(ledger.clone())),
+                        Arc::new(Mutex::new(block)),
+                    )
+                })
+                .clone()
+        })
+    });
+
+    let ledger: StateSource<BalanceLedger> = {
+        let session = session.clone();
+        Arc::new(move || session().0)
+    };
+    let block: StateSource<BlockInfo> = Arc::new(move || session().1);
+
+    let mut natives = ledger_natives(ledger.clone());
+    natives.extend(ledger_testing_natives(ledger));
+    natives.extend(block_natives(block));
+
+    Ok(natives)
+}
+
+/// Makes the unit test runner start a new session of the mocked natives for every test.
+///
+/// The runner creates the native extensions when it starts a test's session.
+pub(crate) fn reset_session_per_unit_test() {
+    move_unit_test::extensions::set_extension_hook(Box::new(
+        |_: &mut NativeContextExtensions<'_>| reset_session(),
+    ));
+}
+
+/// Starts a new session of the mocked natives on the current thread, the following execution
+/// sees the configured values again.
+pub(crate) fn reset_session() {
+    SESSION.with(|session| session.borrow_mut().take());
+}
+
+/// Returns the substrate-stdlib balance natives operating on the ledger.
+pub(crate) fn balance_natives(ledger: SharedLedger) -> NativeFunctionTable {
+    ledger_natives(Arc::new(move || ledger.clone()))
+}
+
+/// Returns the balance natives operating on the ledger from the source.
+fn ledger_natives(ledger: StateSource<BalanceLedger>) -> NativeFunctionTable {
+    let natives: [(&str, NativeFunction); 3] = [
+        ("transfer", {
+            let ledger = ledger.clone();
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let amount = pop_arg!(args, u128);
+                    let dst = pop_arg!(args, AccountAddress);
+                    let src = signer_address(pop_arg!(args, SignerRef))?;
+
+                    let transferred = ledger().lock().unwrap().transfer(src, dst, amount);
+                    Ok(NativeResult::ok(
+                        InternalGas::new(TRANSFER_COST),
+                        smallvec![Value::bool(transferred)],
+                    ))
+                },
+            )
+        }),
+        ("cheque_amount", {
+            let ledger = ledger.clone();
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let account = pop_arg!(args, AccountAddress);
+
+                    let amount = ledger().lock().unwrap().cheques.get(&account).copied();
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![Value::u128(amount.unwrap_or_default())],
+                    ))
+                },
+            )
+        }),
+        ("total_amount", {
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let account = pop_arg!(args, AccountAddress);
+
+                    let amount = ledger().lock().unwrap().balances.get(&account).copied();
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![Value::u128(amount.unwrap_or_default())],
+                    ))
+                },
+            )
+        }),
+    ];
+
+    native_table(BALANCE_MODULE, natives)
+}
+
+/// Returns the test-only balance natives setting the balances and cheques of the session.
+fn ledger_testing_natives(ledger: StateSource<BalanceLedger>) -> NativeFunctionTable {
+    let natives: [(&str, NativeFunction); 2] = [
+        ("set_balance_for_testing", {
+            let ledger = ledger.clone();
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let amount = pop_arg!(args, u128);
+                    let account = pop_arg!(args, AccountAddress);
+
+                    ledger().lock().unwrap().balances.insert(account, amount);
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![],
+                    ))
+                },
+            )
+        }),
+        ("set_cheque_for_testing", {
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let amount = pop_arg!(args, u128);
+                    let account = pop_arg!(args, AccountAddress);
+
+                    ledger().lock().unwrap().cheques.insert(account, amount);
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![],
+                    ))
+                },
+            )
+        }),
+    ];
+
+    native_table(BALANCE_MODULE, natives)
+}
+
+/// Returns the block info natives and the test-only natives setting the block info.
+fn block_natives(block: StateSource<BlockInfo>) -> NativeFunctionTable {
+    let natives: [(&str, NativeFunction); 4] = [
+        ("block_number", {
+            let block = block.clone();
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, _args: VecDeque<Value>| {
+                    let number = block().lock().unwrap().number;
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![Value::u64(number)],
+                    ))
+                },
+            )
+        }),
+        ("timestamp", {
+            let block = block.clone();
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, _args: VecDeque<Value>| {
+                    let timestamp = block().lock().unwrap().timestamp;
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![Value::u64(timestamp)],
+                    ))
+                },
+            )
+        }),
+        ("set_block_number_for_testing", {
+            let block = block.clone();
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let number = pop_arg!(args, u64);
+
+                    block().lock().unwrap().number = number;
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![],
+                    ))
+                },
+            )
+        }),
+        ("set_timestamp_for_testing", {
+            Arc::new(
+                move |_ctx: &mut NativeContext, _ty_args: Vec<Type>, mut args: VecDeque<Value>| {
+                    let timestamp = pop_arg!(args, u64);
+
+                    block().lock().unwrap().timestamp = timestamp;
+                    Ok(NativeResult::ok(
+                        InternalGas::new(STATE_ACCESS_COST),
+                        smallvec![],
+                    ))
+                },
+            )
+        }),
+    ];
+
+    native_table(BLOCK_MODULE, natives)
+}
+
+/// Registers the natives under the substrate-stdlib module.
+fn native_table<const N: usize>(
+    module: &str,
+    natives: [(&str, NativeFunction); N],
+) -> NativeFunctionTable {
+    natives
+        .into_iter()
+        .map(|(name, native)| {
+            // Use unwrap: the names are valid identifiers.
+            (
+                AccountAddress::ONE,
+                Identifier::new(module).unwrap(),
+                Identifier::new(name).unwrap(),
+                native,
+            )
+        })
+        .collect()
+}
+
+/// Reads the address of the signer.
+fn signer_address(signer: SignerRef) -> PartialVMResult<AccountAddress> {
+    signer
+        .borrow_signer()?
+        .value_as::<Reference>()?
+        .read_ref()?
+        .value_as::<AccountAddress>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(address: &str) -> AccountAddress {
+        AccountAddress::from_hex_literal(address).unwrap()
+    }
+
+    /// Loads the mocked values from the manifest with the content.
+    fn load(name: &str, manifest: &str) -> Result<NativesMock> {
+        let path = std::env::temp_dir().join(format!("smove-{name}-{}.toml", std::process::id()));
+        fs::write(&path, manifest).unwrap();
+        let mock = NativesMock::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        mock
+    }
+
+    #[test]
+    fn natives_table() {
+        let mock = load(
+            "natives-table",
+            r#"
+            [package]
+            name = "natives"
+            version = "0.0.0"
+
+            [natives]
+            block_number = 42
+            timestamp = 1000
+
+            [natives.balances]
+            "0xA" = 100
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(mock.balances, BTreeMap::from([("0xA".to_string(), 100)]));
+        assert!(mock.cheques.is_empty());
+        assert_eq!((mock.block_number, mock.timestamp), (42, 1000));
+    }
+
+    #[test]
+    fn manifest_without_natives() {
+        let mock = load("no-natives", "[package]\nname = \"natives\"\n").unwrap();
+
+        assert!(mock.balances.is_empty());
+        assert_eq!(mock.block_number, 0);
+        assert!(NativesMock::load(Path::new("/nonexistent/Move.toml")).is_ok());
+    }
+
+    #[test]
+    fn invalid_manifest() {
+        let syntax_error = load("syntax-error", "[package\n").unwrap_err();
+        assert!(format!("{syntax_error:#}").starts_with("Invalid manifest"));
+
+        let unknown_field = load("unknown-field", "[natives]\nblock = 1\n").unwrap_err();
+        assert!(format!("{unknown_field:#}").starts_with("Invalid [natives] table"));
+    }
+
+    #[test]
+    fn transfer_within_the_cheque_and_balance() {
+        let (alice, bob) = (address("0xA"), address("0xB"));
+        let mut ledger = BalanceLedger {
+            balances: BTreeMap::from([(alice, 100)]),
+            cheques: BTreeMap::from([(alice, 60)]),
+            ..Default::default()
+        };
+
+        assert!(ledger.transfer(alice, bob, 50));
+        assert_eq!(ledger.balances, BTreeMap::from([(alice, 50), (bob, 50)]));
+        assert_eq!(ledger.cheques[&alice], 10);
+        assert_eq!(ledger.transfers.len(), 1);
+        assert_eq!(
+            (ledger.transfers[0].src, ledger.transfers[0].dst),
+            (alice, bob)
+        );
+    }
+
+    #[test]
+    fn transfer_beyond_the_cheque_or_balance() {
+        let (alice, bob) = (address("0xA"), address("0xB"));
+        let mut ledger = BalanceLedger {
+            balances: BTreeMap::from([(alice, 100), (bob, 10)]),
+            cheques: BTreeMap::from([(alice, 60), (bob, 50)]),
+            ..Default::default()
+        };
+
+        // Alice's cheque doesn't cover it.
+        assert!(!ledger.transfer(alice, bob, 61));
+        // Bob's balance doesn't cover it.
+        assert!(!ledger.transfer(bob, alice, 11));
+        assert_eq!(ledger.balances, BTreeMap::from([(alice, 100), (bob, 10)]));
+        assert!(ledger.transfers.is_empty());
+    }
+}
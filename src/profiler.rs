@@ -0,0 +1,510 @@
+//! Gas profiling of the executions with the embedded MoveVM.
+//!
+//! The profiling gas meter wraps the regular gas meter and attributes every charge to the stack
+//! of function frames it happened in. The charges are measured in the external gas units, so the
+//! frames add up to the used gas reported for the execution. The MoveVM reports a call before the callee runs and the
+//! `Ret` instruction when a bytecode function returns, while a native function is finished once
+//! its cost is charged.
+
+use anyhow::{Context, Result};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::gas_algebra::{InternalGas, NumArgs, NumBytes};
+use move_core_types::language_storage::ModuleId;
+use move_vm_test_utils::gas_schedule::GasStatus;
+use move_vm_types::gas::{GasMeter, SimpleInstruction};
+use move_vm_types::views::{TypeView, ValueView};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// Name of the script's frame, the root of every stack.
+const SCRIPT_FRAME: &str = "script";
+
+/// Separator of the frames in the folded stacks.
+const FRAME_SEPARATOR: char = ';';
+
+/// Gas meter attributing the charges of the wrapped meter to the function frames.
+pub(crate) struct ProfilingGasMeter<'a> {
+    /// Wrapped gas meter.
+    inner: GasStatus<'a>,
+    /// Folded stacks of the active frames, from the script to the current frame.
+    stacks: Vec<String>,
+    /// Gas profile collected so far.
+    profile: Profile,
+}
+
+/// Gas used per function frame.
+#[derive(Default)]
+pub(crate) struct Profile {
+    /// Gas used by the frame on top of each folded stack.
+    folded: BTreeMap<String, u64>,
+    /// Number of calls per function.
+    calls: BTreeMap<String, u64>,
+}
+
+/// Gas used by a function.
+pub(crate) struct FunctionGas {
+    /// Fully qualified function name.
+    pub(crate) name: String,
+    /// Number of calls.
+    pub(crate) calls: u64,
+    /// Gas used by the function's own instructions.
+    pub(crate) self_gas: u64,
+    /// Gas used by the function and its callees.
+    pub(crate) total_gas: u64,
+}
+
+impl<'a> ProfilingGasMeter<'a> {
+    /// Creates the profiling meter wrapping the gas meter.
+    pub(crate) fn new(inner: GasStatus<'a>) -> Self {
+        Self {
+            inner,
+            stacks: vec![SCRIPT_FRAME.to_string()],
+            profile: Profile::default(),
+        }
+    }
+
+    /// Returns the wrapped gas meter and the collected profile.
+    pub(crate) fn finish(self) -> (GasStatus<'a>, Profile) {
+        (self.inner, self.profile)
+    }
+
+    /// Charges the wrapped meter and attributes the gas used to the current frame.
+    ///
+    /// The remaining gas is read in the external units (rounded down from the internal ones), so
+    /// the charges of all frames add up to the gas used by the execution.
+    fn charge(
+        &mut self,
+        charge: impl FnOnce(&mut GasStatus<'a>) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        let before = u64::from(GasStatus::remaining_gas(&self.inner));
+        let result = charge(&mut self.inner);
+        let used = before.saturating_sub(u64::from(GasStatus::remaining_gas(&self.inner)));
+
+        let stack = self
+            .stacks
+            .last()
+            .expect("the script frame is never popped");
+        match self.profile.folded.get_mut(stack) {
+            Some(gas) => *gas += used,
+            None => {
+                self.profile.folded.insert(stack.clone(), used);
+            }
+        }
+
+        result
+    }
+
+    /// Enters the frame of the called function.
+    fn push_frame(&mut self, module_id: &ModuleId, func_name: &str) {
+        let name = format!(
+            "{}::{}::{func_name}",
+            module_id.address().to_hex_literal(),
+            module_id.name()
+        );
+        let stack = self
+            .stacks
+            .last()
+            .expect("the script frame is never popped");
+        let stack = format!("{stack}{FRAME_SEPARATOR}{name}");
+
+        *self.profile.calls.entry(name).or_default() += 1;
+        self.stacks.push(stack);
+    }
+
+    /// Leaves the current frame, the script's frame is kept.
+    fn pop_frame(&mut self) {
+        if self.stacks.len() > 1 {
+            self.stacks.pop();
+        }
+    }
+}
+
+impl GasMeter for ProfilingGasMeter<'_> {
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        let is_ret = matches!(instr, SimpleInstruction::Ret);
+        let result = self.charge(|gas| gas.charge_simple_instr(instr));
+        if is_ret {
+            self.pop_frame();
+        }
+
+        result
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_pop(popped_val))
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let result = self.charge(|gas| gas.charge_call(module_id, func_name, args, num_locals));
+        self.push_frame(module_id, func_name);
+
+        result
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let result = self
+            .charge(|gas| gas.charge_call_generic(module_id, func_name, ty_args, args, num_locals));
+        self.push_frame(module_id, func_name);
+
+        result
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_ld_const(size))
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_ld_const_after_deserialization(val))
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_copy_loc(val))
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_move_loc(val))
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_store_loc(val))
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_pack(is_generic, args))
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_unpack(is_generic, args))
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_read_ref(val))
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_write_ref(new_val, old_val))
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_eq(lhs, rhs))
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_neq(lhs, rhs))
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_borrow_global(is_mut, is_generic, ty, is_success))
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_exists(is_generic, ty, exists))
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_move_from(is_generic, ty, val))
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_move_to(is_generic, ty, val, is_success))
+    }
+
+    fn charge_vec_pack<'a>(
+        &mut self,
+        ty: impl TypeView + 'a,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_pack(ty, args))
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_len(ty))
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_borrow(is_mut, ty, is_success))
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_push_back(ty, val))
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_pop_back(ty, val))
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_unpack(ty, expect_num_elements, elems))
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_vec_swap(ty))
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_load_resource(loaded))
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let result = self.charge(|gas| gas.charge_native_function(amount, ret_vals));
+        // Natives don't execute the `Ret` instruction, their frame ends with the charge.
+        self.pop_frame();
+
+        result
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_native_function_before_execution(ty_args, args))
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas| gas.charge_drop_frame(locals))
+    }
+
+    fn remaining_gas(&self) -> InternalGas {
+        GasMeter::remaining_gas(&self.inner)
+    }
+}
+
+impl Profile {
+    /// Writes the folded stacks with the gas used, the input format of the flame graph tools.
+    pub(crate) fn write_folded(&self, path: &Path) -> Result<()> {
+        let folded = self
+            .folded
+            .iter()
+            .filter(|(_, gas)| **gas > 0)
+            .map(|(stack, gas)| format!("{stack} {gas}\n"))
+            .collect::<String>();
+
+        fs::write(path, folded).with_context(|| format!("Failure to write {}", path.display()))
+    }
+
+    /// Returns the gas used per function, the most expensive functions in total first.
+    pub(crate) fn functions(&self) -> Vec<FunctionGas> {
+        let mut functions = BTreeMap::<&str, FunctionGas>::new();
+        for (stack, gas) in self.folded.iter() {
+            let frames = stack.split(FRAME_SEPARATOR).collect::<Vec<_>>();
+
+            // Recursive calls must not count the gas more than once per stack.
+            for frame in frames.iter().copied().collect::<BTreeSet<_>>() {
+                functions
+                    .entry(frame)
+                    .or_insert_with(|| FunctionGas {
+                        name: frame.to_string(),
+                        calls: self.calls.get(frame).copied().unwrap_or(1),
+                        self_gas: 0,
+                        total_gas: 0,
+                    })
+                    .total_gas += gas;
+            }
+            if let Some(frame) = frames.last() {
+                if let Some(function) = functions.get_mut(*frame) {
+                    function.self_gas += gas;
+                }
+            }
+        }
+
+        let mut functions = functions.into_values().collect::<Vec<_>>();
+        functions.sort_by(|a, b| {
+            (b.total_gas, b.self_gas)
+                .cmp(&(a.total_gas, a.self_gas))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        functions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::account_address::AccountAddress;
+    use move_core_types::identifier::Identifier;
+    use move_vm_backend_common::gas_schedule::INSTRUCTION_COST_TABLE;
+    use move_vm_test_utils::gas_schedule::Gas;
+    use move_vm_types::values::Value;
+
+    fn profile(folded: &[(&str, u64)], calls: &[(&str, u64)]) -> Profile {
+        let to_map = |entries: &[(&str, u64)]| {
+            entries
+                .iter()
+                .map(|(name, gas)| (name.to_string(), *gas))
+                .collect()
+        };
+
+        Profile {
+            folded: to_map(folded),
+            calls: to_map(calls),
+        }
+    }
+
+    #[test]
+    fn functions_are_sorted_by_total_gas() {
+        let profile = profile(
+            &[
+                ("script", 10),
+                ("script;0x1::coin::transfer", 30),
+                ("script;0x1::coin::transfer;0x1::balance::sub", 50),
+                ("script;0x1::vector::length", 5),
+            ],
+            &[
+                ("0x1::coin::transfer", 1),
+                ("0x1::balance::sub", 2),
+                ("0x1::vector::length", 3),
+            ],
+        );
+
+        let functions = profile.functions();
+        let summary = functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.calls, f.self_gas, f.total_gas))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            [
+                ("script", 1, 10, 95),
+                ("0x1::coin::transfer", 1, 30, 80),
+                ("0x1::balance::sub", 2, 50, 50),
+                ("0x1::vector::length", 3, 5, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn recursive_calls_count_the_gas_once_per_stack() {
+        let profile = profile(
+            &[
+                ("script;0x1::math::fact", 10),
+                ("script;0x1::math::fact;0x1::math::fact", 10),
+            ],
+            &[("0x1::math::fact", 2)],
+        );
+
+        let functions = profile.functions();
+        let fact = functions
+            .iter()
+            .find(|f| f.name == "0x1::math::fact")
+            .unwrap();
+
+        assert_eq!((fact.calls, fact.self_gas, fact.total_gas), (2, 20, 20));
+    }
+
+    #[test]
+    fn frames_add_up_to_the_used_gas() {
+        let gas_limit = 1_000_000;
+        let mut meter =
+            ProfilingGasMeter::new(GasStatus::new(&INSTRUCTION_COST_TABLE, Gas::new(gas_limit)));
+        let module = ModuleId::new(AccountAddress::ONE, Identifier::new("math").unwrap());
+        let no_values = || std::iter::empty::<Value>();
+
+        for _ in 0..3 {
+            meter.charge_simple_instr(SimpleInstruction::LdU64).unwrap();
+            meter
+                .charge_call(&module, "double", no_values(), NumArgs::new(1))
+                .unwrap();
+            meter.charge_simple_instr(SimpleInstruction::Add).unwrap();
+            meter
+                .charge_call(&module, "native", no_values(), NumArgs::new(0))
+                .unwrap();
+            // Not a multiple of the internal units per gas unit, so the charges get rounded.
+            meter
+                .charge_native_function(InternalGas::new(1_234), Some(no_values()))
+                .unwrap();
+            meter.charge_simple_instr(SimpleInstruction::Ret).unwrap();
+        }
+
+        let (gas_status, profile) = meter.finish();
+        let gas_used = gas_limit - u64::from(gas_status.remaining_gas());
+        let functions = profile.functions();
+        let script = functions.iter().find(|f| f.name == SCRIPT_FRAME).unwrap();
+
+        assert!(gas_used > 0);
+        assert_eq!(profile.folded.values().sum::<u64>(), gas_used);
+        assert_eq!(script.total_gas, gas_used);
+        assert_eq!(profile.calls.get("0x1::math::double").copied(), Some(3));
+    }
+}
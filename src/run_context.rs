@@ -1,18 +1,33 @@
+use crate::config::SmoveConfig;
+use crate::gas_schedule::{self, GasSchedule};
+use crate::natives::{mocked_natives, NativesMock};
 use anyhow::{Error, Result};
+use move_binary_format::CompiledModule;
 use move_cli::Move as MoveCliArgs;
-use move_command_line_common::files::{extension_equals, find_filenames, MOVE_COMPILED_EXTENSION};
+use move_command_line_common::files::{
+    extension_equals, find_filenames, MOVE_COMPILED_EXTENSION, MOVE_ERROR_DESC_EXTENSION,
+};
 use move_core_types::errmap::ErrorMapping;
 use move_core_types::language_storage::CORE_CODE_ADDRESS;
 use move_package::compilation::package_layout::CompiledPackageLayout;
 use move_package::source_package::parsed_manifest::SourceManifest;
 use move_package::source_package::{layout, manifest_parser};
 use move_stdlib::natives::all_natives;
-use move_vm_backend_common::gas_schedule::{INSTRUCTION_COST_TABLE, NATIVE_COST_PARAMS};
 use move_vm_runtime::native_functions::NativeFunctionTable;
 use move_vm_test_utils::gas_schedule::CostTable;
+use std::cell::OnceCell;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Default file name of the package's error map.
+const PACKAGE_ERRMAP_NAME: &str = "error_map";
+
+/// Extension for bundle files.
+const BUNDLE_EXTENSION: &str = "mvb";
+
+/// Extension for script transaction files.
+const SCRIPT_TX_EXTENSION: &str = "mvt";
+
 /// Move compilation related data.
 pub struct RunContext {
     /// Project directory.
@@ -21,31 +36,59 @@ pub struct RunContext {
     pub move_args: MoveCliArgs,
     /// Error descriptions.
     pub error_descriptions: ErrorMapping,
-    /// Native functions.
-    pub natives: NativeFunctionTable,
+    /// Error descriptions generated for the package by the `errmap` command.
+    pub package_error_descriptions: Option<ErrorMapping>,
+    /// Move standard library natives.
+    stdlib_natives: NativeFunctionTable,
+    /// Native functions including the mocked substrate-stdlib natives, loaded on the first use so
+    /// a broken `[natives]` table affects only the commands executing Move code.
+    natives: OnceCell<NativeFunctionTable>,
     /// Cost table.
     pub cost_table: CostTable,
+    /// smove configuration, loaded on the first use so a broken `smove.toml` affects only the
+    /// commands using it.
+    config: OnceCell<SmoveConfig>,
     /// `Move.toml` contents for the current folder.
     manifest: Option<SourceManifest>,
 }
 
 impl RunContext {
     /// Create a new instance.
-    pub fn new(project_root_dir: PathBuf, move_args: MoveCliArgs) -> Result<Self> {
-        let cost_table = INSTRUCTION_COST_TABLE.clone();
-        let natives = all_natives(CORE_CODE_ADDRESS, NATIVE_COST_PARAMS.clone());
+    ///
+    /// The gas schedule file replaces the built-in cost table and native function parameters.
+    pub fn new(
+        project_root_dir: PathBuf,
+        move_args: MoveCliArgs,
+        gas_schedule: Option<&Path>,
+    ) -> Result<Self> {
+        let schedule = match gas_schedule {
+            Some(path) => gas_schedule::load(path)?,
+            None => GasSchedule::built_in(),
+        };
+        let stdlib_natives = all_natives(CORE_CODE_ADDRESS, schedule.native_params()?);
         let error_descriptions = bcs::from_bytes(move_stdlib::doc::error_descriptions())?;
 
         let manifest_path = project_root_dir.join(layout::SourcePackageLayout::Manifest.path());
         let manifest = manifest_parser::parse_move_manifest_from_file(&manifest_path).ok();
 
+        // The error map is generated by the `smove errmap` command in the package directory.
+        let errmap_path = project_root_dir
+            .join(PACKAGE_ERRMAP_NAME)
+            .with_extension(MOVE_ERROR_DESC_EXTENSION);
+        let package_error_descriptions = fs::read(errmap_path)
+            .ok()
+            .and_then(|bytes| bcs::from_bytes(&bytes).ok());
+
         Ok(Self {
             project_root_dir,
             move_args,
             manifest,
             error_descriptions,
-            natives,
-            cost_table,
+            package_error_descriptions,
+            stdlib_natives,
+            natives: OnceCell::new(),
+            cost_table: schedule.cost_table,
+            config: OnceCell::new(),
         })
     }
 
@@ -57,40 +100,92 @@ impl RunContext {
         )))
     }
 
-    /// Path where bundles are generated.
-    pub fn bundle_output_path(&self, bundle_name: &impl AsRef<Path>) -> Result<PathBuf, Error> {
+    /// Get the smove configuration, loading it on the first use.
+    pub fn config(&self) -> Result<&SmoveConfig> {
+        if let Some(config) = self.config.get() {
+            return Ok(config);
+        }
+
+        let config = SmoveConfig::load(&self.project_root_dir)?;
+        Ok(self.config.get_or_init(|| config))
+    }
+
+    /// Get the native functions, loading the mocked values from the manifest on the first use.
+    pub fn natives(&self) -> Result<&NativeFunctionTable> {
+        if let Some(natives) = self.natives.get() {
+            return Ok(natives);
+        }
+
+        let manifest_path = self
+            .project_root_dir
+            .join(layout::SourcePackageLayout::Manifest.path());
+        let mut natives = self.stdlib_natives.clone();
+        natives.extend(mocked_natives(&NativesMock::load(&manifest_path)?)?);
+
+        Ok(self.natives.get_or_init(|| natives))
+    }
+
+    /// Get all available error maps, the package's own error map comes first.
+    pub fn error_maps(&self) -> Vec<&ErrorMapping> {
+        self.package_error_descriptions
+            .iter()
+            .chain([&self.error_descriptions])
+            .collect()
+    }
+
+    /// Directory "<PACKAGE_PATH>/build/<PACKAGE_NAME>/bundles/" where bundles are generated.
+    pub fn bundles_dir(&self) -> Result<PathBuf, Error> {
         let package_name = self.manifest()?.package.name.as_str();
 
-        // Create directory "<PACKAGE_PATH>/build/<PACKAGE_NAME>/bundles/"
-        let dir = self
+        Ok(self
             .project_root_dir
             .join(CompiledPackageLayout::Root.path())
             .join(package_name)
-            .join("bundles");
+            .join("bundles"))
+    }
+
+    /// Directory "<PACKAGE_PATH>/build/<PACKAGE_NAME>/script_transactions/" where script
+    /// transactions are generated.
+    pub fn script_tx_dir(&self) -> Result<PathBuf, Error> {
+        let package_name = self.manifest()?.package.name.as_str();
+
+        Ok(self
+            .project_root_dir
+            .join(CompiledPackageLayout::Root.path())
+            .join(package_name)
+            .join("script_transactions"))
+    }
+
+    /// Path where bundles are generated.
+    pub fn bundle_output_path(&self, bundle_name: &impl AsRef<Path>) -> Result<PathBuf, Error> {
+        let dir = self.bundles_dir()?;
 
         if !dir.exists() {
             fs::create_dir_all(&dir)?;
         }
 
-        Ok(dir.join(bundle_name).with_extension("mvb"))
+        Ok(dir.join(bundle_name).with_extension(BUNDLE_EXTENSION))
     }
 
     /// Path where script transactions are generated.
     pub fn script_tx_output_path(&self, tx_name: &impl AsRef<Path>) -> Result<PathBuf, Error> {
-        let package_name = self.manifest()?.package.name.as_str();
-
-        // Create directory "<PACKAGE_PATH>/build/<PACKAGE_NAME>/script_transactions/"
-        let dir = self
-            .project_root_dir
-            .join(CompiledPackageLayout::Root.path())
-            .join(package_name)
-            .join("script_transactions");
+        let dir = self.script_tx_dir()?;
 
         if !dir.exists() {
             fs::create_dir_all(&dir)?;
         }
 
-        Ok(dir.join(tx_name).with_extension("mvt"))
+        Ok(dir.join(tx_name).with_extension(SCRIPT_TX_EXTENSION))
+    }
+
+    /// Get paths for all generated bundles.
+    pub fn get_bundles(&self) -> Result<Vec<PathBuf>> {
+        find_files_with_extension(&self.bundles_dir()?, BUNDLE_EXTENSION)
+    }
+
+    /// Get paths for all generated script transactions.
+    pub fn get_script_transactions(&self) -> Result<Vec<PathBuf>> {
+        find_files_with_extension(&self.script_tx_dir()?, SCRIPT_TX_EXTENSION)
     }
 
     /// Get paths for all compiled modules without dependencies.
@@ -108,4 +203,51 @@ impl RunContext {
 
         Ok(files.into_iter().map(PathBuf::from).collect())
     }
+
+    /// Load the compiled modules of the package and its dependencies.
+    pub fn get_compiled_modules(&self) -> Result<Vec<CompiledModule>> {
+        let mut paths = self.get_dependency_modules()?;
+        paths.extend(self.get_bytecode_modules()?);
+
+        paths
+            .iter()
+            .map(|path| {
+                CompiledModule::deserialize(&fs::read(path)?)
+                    .map_err(|e| Error::msg(format!("Invalid module {}: {e:?}", path.display())))
+            })
+            .collect()
+    }
+
+    /// Get paths for all compiled modules of the package's dependencies.
+    pub fn get_dependency_modules(&self) -> Result<Vec<PathBuf>> {
+        let dependencies_dir = CompiledPackageLayout::Dependencies.path().as_os_str();
+
+        let files = find_filenames(&[&self.project_root_dir], |path| {
+            // Dependencies are stored as "bytecode_modules/dependencies/<DEPENDENCY>/<MODULE>.mv".
+            let grandparent_dir = path
+                .parent()
+                .and_then(|path| path.parent())
+                .and_then(|path| path.file_name());
+
+            extension_equals(path, MOVE_COMPILED_EXTENSION)
+                && grandparent_dir == Some(dependencies_dir)
+        })?;
+
+        Ok(files.into_iter().map(PathBuf::from).collect())
+    }
+}
+
+/// Get sorted paths for all files with the extension within the directory.
+fn find_files_with_extension(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut files = find_filenames(&[dir], |path| extension_equals(path, extension))?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    files.sort();
+
+    Ok(files)
 }
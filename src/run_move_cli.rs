@@ -1,5 +1,6 @@
 //! A handler module for move_cli.
 
+use crate::natives::reset_session_per_unit_test;
 use crate::run_context::RunContext;
 use anyhow::Result;
 use move_cli::base::build::Build;
@@ -14,8 +15,12 @@ pub fn execute_build(ctx: &RunContext) -> Result<()> {
 
 /// Execute move_cli subcommand.
 pub fn run_command(ctx: &RunContext, command: Command) -> Result<()> {
+    if let Command::Test(_) = command {
+        reset_session_per_unit_test();
+    }
+
     move_cli::run_cli(
-        ctx.natives.clone(),
+        ctx.natives()?.clone(),
         &ctx.cost_table,
         &ctx.error_descriptions,
         &ctx.move_args,
@@ -0,0 +1,62 @@
+//! Helpers shared by the integration tests running the smove binary.
+
+// Every test binary uses only some of the helpers.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Creates the package with the files (relative paths and contents) in a new temporary directory.
+pub fn create_package(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let package = std::env::temp_dir().join(format!("smove-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&package);
+
+    for (path, content) in files {
+        let path = package.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    package
+}
+
+/// Creates the smove command running in the package directory.
+pub fn smove(package: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_smove"));
+    command.current_dir(package);
+    command
+}
+
+/// Runs the smove command and returns its output, failing the test if the command fails.
+pub fn run(package: &Path, args: &[&str]) -> String {
+    let output = smove(package).args(args).output().unwrap();
+    assert!(
+        output.status.success(),
+        "smove {args:?} failed:\n{}",
+        output_text(&output)
+    );
+
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Runs the smove command and returns its output, failing the test if the command succeeds.
+pub fn run_failing(package: &Path, args: &[&str]) -> String {
+    let output = smove(package).args(args).output().unwrap();
+    assert!(
+        !output.status.success(),
+        "smove {args:?} succeeded:\n{}",
+        output_text(&output)
+    );
+
+    output_text(&output)
+}
+
+/// Returns the standard output followed by the error output.
+fn output_text(output: &Output) -> String {
+    format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}
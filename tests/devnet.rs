@@ -0,0 +1,149 @@
+//! Publishes and executes a package on the local devnet.
+
+mod common;
+
+use common::{create_package, run, run_failing};
+use std::fs;
+
+const MANIFEST: &str = r#"
+[package]
+name = "devnet"
+version = "0.0.0"
+
+[addresses]
+devnet = "0xCAFE"
+"#;
+
+const MODULE: &str = r#"
+module devnet::counter {
+    struct Counter has key {
+        value: u64,
+    }
+
+    public fun publish(account: &signer, value: u64) {
+        move_to(account, Counter { value });
+    }
+}
+"#;
+
+const SCRIPT: &str = r#"
+script {
+    use devnet::counter;
+
+    fun publish_counter(account: signer, value: u64) {
+        counter::publish(&account, value);
+    }
+}
+"#;
+
+const COUNTER: &str = "0xCAFE::counter::Counter";
+
+#[test]
+fn devnet_workflow() {
+    let package = create_package(
+        "devnet",
+        &[
+            ("Move.toml", MANIFEST),
+            ("sources/counter.move", MODULE),
+            ("sources/publish_counter.move", SCRIPT),
+        ],
+    );
+    let tx = "build/devnet/script_transactions/publish_counter.mvt";
+
+    run(&package, &["build"]);
+    run(
+        &package,
+        &[
+            "create-transaction",
+            "--compiled-script-path",
+            "build/devnet/bytecode_scripts/publish_counter.mv",
+            "--args",
+            "signer:0xCAFE",
+            "u64:7",
+        ],
+    );
+    run(&package, &["devnet", "init"]);
+
+    // The modules' address must match the sender.
+    let module = "build/devnet/bytecode_modules/counter.mv";
+    let mismatch = run(
+        &package,
+        &["devnet", "publish", "-m", module, "-s", "0xBEEF"],
+    );
+    assert!(mismatch.contains("vm_status_code: MODULE_ADDRESS_DOES_NOT_MATCH_SENDER"));
+    assert!(!package.join(".devnet/0xbeef").exists());
+    let publish = run(
+        &package,
+        &["devnet", "publish", "-m", module, "-s", "0xCAFE"],
+    );
+    assert!(publish.contains("vm_status_code: EXECUTED"));
+    assert!(package.join(".devnet/0xcafe/modules/counter.mv").exists());
+
+    let balance = run(
+        &package,
+        &["devnet", "balance", "-a", "0xCAFE", "--set", "100"],
+    );
+    assert!(balance.contains("Balance of 0xcafe: 100"));
+
+    let missing = run(
+        &package,
+        &["devnet", "get-resource", "-a", "0xCAFE", "-t", COUNTER],
+    );
+    assert!(missing.contains("Resource 0xcafe::counter::Counter not found under 0xcafe"));
+
+    // All script signers must sign.
+    let unsigned = run_failing(&package, &["devnet", "execute", "-s", tx]);
+    assert!(unsigned.contains("requires the signature of 0xcafe"));
+    let wrong_signer = run_failing(
+        &package,
+        &["devnet", "execute", "-s", tx, "--signer", "0xBEEF"],
+    );
+    assert!(wrong_signer.contains("requires the signature of 0xcafe"));
+
+    // The balance must cover the cash limit.
+    let over_limit = run_failing(
+        &package,
+        &[
+            "devnet",
+            "execute",
+            "-s",
+            tx,
+            "--signer",
+            "0xCAFE",
+            "--cash-limit",
+            "101",
+        ],
+    );
+    assert!(over_limit.contains("Insufficient balance of 0xcafe for the cash limit 101: 100"));
+
+    // The module is loaded from the storage written by the previous commands.
+    let execute = run(
+        &package,
+        &[
+            "devnet",
+            "execute",
+            "-s",
+            tx,
+            "--signer",
+            "0xCAFE",
+            "--cash-limit",
+            "100",
+        ],
+    );
+    assert!(execute.contains("vm_status_code: EXECUTED"));
+    assert!(package
+        .join(".devnet/0xcafe/resources/0xcafe::counter::Counter.bcs")
+        .exists());
+
+    let resource = run(
+        &package,
+        &["devnet", "get-resource", "-a", "0xCAFE", "-t", COUNTER],
+    );
+    assert!(resource.contains(": 0x0700000000000000"));
+
+    // The balances are read back from the devnet directory.
+    let balance = run(&package, &["devnet", "balance", "-a", "0xCAFE"]);
+    assert!(balance.contains("Balance of 0xcafe: 100"));
+
+    fs::remove_dir_all(&package).unwrap();
+}
@@ -0,0 +1,81 @@
+//! Stores and checks the gas snapshot estimated with the embedded MoveVM.
+
+mod common;
+
+use common::{create_package, run, run_failing};
+use std::fs;
+
+const MANIFEST: &str = r#"
+[package]
+name = "gas_snapshot"
+version = "0.0.0"
+
+[addresses]
+snapshot = "0xCAFE"
+"#;
+
+const MODULE: &str = r#"
+module snapshot::counter {
+    public fun count(n: u64): u64 {
+        let i = 0;
+        while (i < n) {
+            i = i + 1;
+        };
+        i
+    }
+}
+"#;
+
+const SCRIPT: &str = r#"
+script {
+    use snapshot::counter;
+
+    fun count(n: u64) {
+        assert!(counter::count(n) == n, 0);
+    }
+}
+"#;
+
+#[test]
+fn offline_gas_snapshot() {
+    let package = create_package(
+        "gas-snapshot",
+        &[
+            ("Move.toml", MANIFEST),
+            ("sources/counter.move", MODULE),
+            ("sources/count.move", SCRIPT),
+        ],
+    );
+    let create_transaction = |n: u64| {
+        run(
+            &package,
+            &[
+                "create-transaction",
+                "--compiled-script-path",
+                "build/gas_snapshot/bytecode_scripts/count.mv",
+                "--args",
+                &format!("u64:{n}"),
+            ],
+        )
+    };
+
+    run(&package, &["build"]);
+    run(&package, &["bundle"]);
+    create_transaction(10);
+
+    run(&package, &["gas-snapshot", "--offline"]);
+    let snapshot = fs::read_to_string(package.join(".gas-snapshot")).unwrap();
+    assert!(snapshot.contains("count.mvt "));
+    assert!(snapshot.contains("gas_snapshot.mvb "));
+
+    let check = run(&package, &["gas-snapshot", "--offline", "--check"]);
+    assert!(check.contains("Gas snapshot check passed"));
+
+    // More iterations use more gas.
+    create_transaction(100);
+    let check = run_failing(&package, &["gas-snapshot", "--offline", "--check"]);
+    assert!(check.contains("count.mvt: "));
+    assert!(check.contains("REGRESSION"));
+
+    fs::remove_dir_all(&package).unwrap();
+}
@@ -0,0 +1,175 @@
+//! Runs the estimation commands against the mock pallet-move node.
+
+mod common;
+
+use common::{create_package, run, smove};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+
+/// Package with a module and a script calling it, without any dependencies to fetch.
+const MANIFEST: &str = r#"
+[package]
+name = "mock_node"
+version = "0.0.0"
+
+[addresses]
+mock = "0xCAFE"
+"#;
+
+const MODULE: &str = r#"
+module mock::math {
+    const EOVERFLOW: u64 = 1;
+
+    public fun double(x: u64): u64 {
+        assert!(x <= 1000, EOVERFLOW);
+        x * 2
+    }
+}
+"#;
+
+const DOUBLE_SCRIPT: &str = r#"
+script {
+    use mock::math;
+
+    fun double(x: u64) {
+        assert!(math::double(x) == x + x, 0);
+    }
+}
+"#;
+
+/// Mock node serving the package, killed when dropped.
+struct MockNode {
+    process: Child,
+    url: String,
+}
+
+impl MockNode {
+    /// Starts the mock node on a free port.
+    fn start(package: &Path) -> Self {
+        let mut process = smove(package)
+            .args(["node", "mock", "--port", "0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut lines = BufReader::new(process.stdout.take().unwrap()).lines();
+        let url = lines
+            .by_ref()
+            .map(|line| line.unwrap())
+            .find_map(|line| {
+                line.strip_prefix("Mock pallet-move RPC server is listening on ")
+                    .map(String::from)
+            })
+            .expect("The mock node didn't start");
+
+        // Keep reading the output, so the mock doesn't fail writing into a closed pipe.
+        std::thread::spawn(move || lines.for_each(drop));
+
+        Self { process, url }
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Creates and builds the package with a script transaction for `double(x)`.
+fn build_package(name: &str, x: u64) -> PathBuf {
+    let package = create_package(
+        name,
+        &[
+            ("Move.toml", MANIFEST),
+            ("sources/math.move", MODULE),
+            ("sources/double.move", DOUBLE_SCRIPT),
+        ],
+    );
+
+    run(&package, &["build"]);
+    run(
+        &package,
+        &[
+            "create-transaction",
+            "--compiled-script-path",
+            "build/mock_node/bytecode_scripts/double.mv",
+            "--args",
+            &format!("u64:{x}"),
+        ],
+    );
+
+    package
+}
+
+/// Returns the used gas printed by the estimation.
+fn used_gas(output: &str) -> u64 {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("used gas: "))
+        .expect("The estimation has no used gas")
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn estimations_match_the_offline_estimation() {
+    let package = build_package("estimate", 21);
+    let node = MockNode::start(&package);
+    let tx = "build/mock_node/script_transactions/double.mvt";
+
+    let offline = run(&package, &["estimate", "--offline", "-s", tx]);
+    let estimate = run(&package, &["estimate", "--url", &node.url, "-s", tx]);
+    let rpc = run(
+        &package,
+        &[
+            "node",
+            "rpc",
+            "estimate-gas-execute-script",
+            "--url",
+            &node.url,
+            "-s",
+            tx,
+            "--fee",
+            "--gas-margin",
+            "10%",
+        ],
+    );
+
+    assert!(offline.contains("vm_status_code: EXECUTED"));
+    assert!(used_gas(&offline) > 0);
+    assert_eq!(used_gas(&estimate), used_gas(&offline));
+    assert_eq!(used_gas(&rpc), used_gas(&offline));
+
+    // The mock derives the weight from the gas, so the fee can be quoted.
+    assert!(rpc.contains("ref_time: "));
+    assert!(rpc.contains("fee: "));
+    assert!(rpc.contains(&format!(
+        "recommended gas_limit: {}",
+        used_gas(&offline) * 110 / 100
+    )));
+
+    drop(node);
+    fs::remove_dir_all(&package).unwrap();
+}
+
+#[test]
+fn aborted_estimation_is_explained() {
+    let package = build_package("abort", 1001);
+    let node = MockNode::start(&package);
+    let tx = "build/mock_node/script_transactions/double.mvt";
+
+    let estimate = run(&package, &["estimate", "--url", &node.url, "-s", tx]);
+    let offline = run(&package, &["estimate", "--offline", "-s", tx]);
+
+    // The mock reports the abort code of the local execution, unlike pallet-move.
+    for output in [estimate, offline] {
+        assert!(output.contains("vm_status_code: ABORTED"));
+        assert!(output.contains("abort code: 1 (category: 1, reason: 0)"));
+    }
+
+    drop(node);
+    fs::remove_dir_all(&package).unwrap();
+}
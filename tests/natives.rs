@@ -0,0 +1,111 @@
+//! Runs the unit tests calling the mocked substrate-stdlib natives.
+
+mod common;
+
+use common::{create_package, run, run_failing};
+use std::fs;
+
+/// Package with the values of the mocked natives.
+const MANIFEST: &str = r#"
+[package]
+name = "natives"
+version = "0.0.0"
+
+[addresses]
+std = "0x1"
+
+[natives]
+block_number = 42
+timestamp = 1000
+
+[natives.balances]
+"0xA" = 100
+
+[natives.cheques]
+"0xA" = 60
+"#;
+
+/// Declarations of the substrate-stdlib natives, so the package doesn't need to fetch it.
+const NATIVES: &str = r#"
+module std::balance {
+    native public fun transfer(src: &signer, dst: address, cheque_amount: u128): bool;
+    native public fun cheque_amount(account: address): u128;
+    native public fun total_amount(account: address): u128;
+
+    #[test_only]
+    native public fun set_cheque_for_testing(account: address, amount: u128);
+}
+
+module std::block {
+    native public fun block_number(): u64;
+    native public fun timestamp(): u64;
+}
+"#;
+
+const TESTS: &str = r#"
+#[test_only]
+module std::natives_tests {
+    use std::balance;
+    use std::block;
+
+    #[test(alice = @0xA)]
+    fun transfer_within_the_cheque(alice: signer) {
+        assert!(balance::transfer(&alice, @0xB, 50), 0);
+        assert!(balance::cheque_amount(@0xA) == 10, 1);
+        assert!(balance::total_amount(@0xA) == 50, 2);
+        assert!(balance::total_amount(@0xB) == 50, 3);
+    }
+
+    // Every test starts from the configured values, so the other test's transfer isn't seen.
+    #[test(alice = @0xA)]
+    fun transfer_beyond_the_cheque(alice: signer) {
+        assert!(balance::cheque_amount(@0xA) == 60, 0);
+        assert!(!balance::transfer(&alice, @0xB, 61), 1);
+        assert!(balance::total_amount(@0xA) == 100, 2);
+
+        balance::set_cheque_for_testing(@0xA, 100);
+        assert!(balance::transfer(&alice, @0xB, 61), 3);
+    }
+
+    #[test]
+    fun block_info() {
+        assert!(block::block_number() == 42, 0);
+        assert!(block::timestamp() == 1000, 1);
+    }
+}
+"#;
+
+#[test]
+fn unit_tests_use_the_mocked_natives() {
+    let package = create_package(
+        "natives",
+        &[
+            ("Move.toml", MANIFEST),
+            ("sources/natives.move", NATIVES),
+            ("sources/natives_tests.move", TESTS),
+        ],
+    );
+
+    let output = run(&package, &["test"]);
+
+    assert!(output.contains("Test result: OK. Total tests: 3; passed: 3; failed: 0"));
+
+    fs::remove_dir_all(&package).unwrap();
+}
+
+#[test]
+fn broken_natives_table_affects_only_move_executions() {
+    let manifest = MANIFEST.replace("block_number", "block");
+    let package = create_package(
+        "broken-natives",
+        &[("Move.toml", &manifest), ("sources/natives.move", NATIVES)],
+    );
+
+    // Commands not executing Move code don't read the table.
+    assert!(run(&package, &["call-hash", "-s", "Move.toml"]).contains("Call hash:"));
+
+    let output = run_failing(&package, &["test"]);
+    assert!(output.contains("Invalid [natives] table"));
+
+    fs::remove_dir_all(&package).unwrap();
+}
@@ -0,0 +1,116 @@
+//! Runs the shipped artifacts in the move-cli sandbox storage.
+
+mod common;
+
+use common::{create_package, run};
+use std::fs;
+
+const MANIFEST: &str = r#"
+[package]
+name = "sandbox"
+version = "0.0.0"
+
+[addresses]
+sandbox = "0xCAFE"
+"#;
+
+const MODULE: &str = r#"
+module sandbox::counter {
+    struct Counter has key {
+        value: u64,
+    }
+
+    public fun publish(account: &signer, value: u64) {
+        move_to(account, Counter { value });
+    }
+
+    public fun bump(addr: address) acquires Counter {
+        let counter = borrow_global_mut<Counter>(addr);
+        assert!(counter.value < 10, 1);
+        counter.value = counter.value + 1;
+    }
+}
+"#;
+
+const PUBLISH_SCRIPT: &str = r#"
+script {
+    use sandbox::counter;
+
+    fun publish_counter(account: signer, value: u64) {
+        counter::publish(&account, value);
+    }
+}
+"#;
+
+const BUMP_SCRIPT: &str = r#"
+script {
+    use sandbox::counter;
+
+    fun bump(addr: address) {
+        counter::bump(addr);
+    }
+}
+"#;
+
+const PUBLISH_TX: &str = "build/sandbox/script_transactions/publish_counter.mvt";
+const BUMP_TX: &str = "build/sandbox/script_transactions/bump.mvt";
+
+#[test]
+fn sandbox_artifacts() {
+    let package = create_package(
+        "sandbox",
+        &[
+            ("Move.toml", MANIFEST),
+            ("sources/counter.move", MODULE),
+            ("sources/publish_counter.move", PUBLISH_SCRIPT),
+            ("sources/bump.move", BUMP_SCRIPT),
+        ],
+    );
+
+    run(&package, &["build"]);
+    run(&package, &["bundle"]);
+    for (script, args) in [
+        ("publish_counter", &["signer:0xCAFE", "u64:9"][..]),
+        ("bump", &["address:0xCAFE"][..]),
+    ] {
+        let script_path = format!("build/sandbox/bytecode_scripts/{script}.mv");
+        let mut cmd = vec![
+            "create-transaction",
+            "--compiled-script-path",
+            script_path.as_str(),
+        ];
+        cmd.push("--args");
+        cmd.extend(args);
+        run(&package, &cmd);
+    }
+
+    let publish = run(
+        &package,
+        &[
+            "sandbox",
+            "publish-bundle",
+            "build/sandbox/bundles/sandbox.mvb",
+        ],
+    );
+    assert!(publish.contains("vm_status_code: EXECUTED"));
+    let execute = run(&package, &["sandbox", "run-transaction", PUBLISH_TX]);
+    assert!(execute.contains("vm_status_code: EXECUTED"));
+
+    // Every execution reads the counter stored by the previous one.
+    let bump = run(&package, &["sandbox", "run-transaction", BUMP_TX]);
+    assert!(bump.contains("vm_status_code: EXECUTED"));
+    let bump = run(&package, &["sandbox", "run-transaction", BUMP_TX]);
+    assert!(bump.contains("vm_status_code: ABORTED"));
+
+    // The move-cli sandbox subcommands share the storage.
+    let storage = ["sandbox", "--storage-dir", "other-storage"];
+    run(&package, &[&storage[..], &["publish"][..]].concat());
+    assert!(package.join("other-storage").exists());
+    let execute = run(
+        &package,
+        &[&storage[..], &["run-transaction", PUBLISH_TX][..]].concat(),
+    );
+    assert!(execute.contains("vm_status_code: EXECUTED"));
+
+    fs::remove_dir_all(&package).unwrap();
+}
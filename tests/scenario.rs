@@ -0,0 +1,147 @@
+//! Runs deployment scenarios with the embedded MoveVM.
+
+mod common;
+
+use common::{create_package, run, run_failing};
+use std::fs;
+
+const MANIFEST: &str = r#"
+[package]
+name = "scenario"
+version = "0.0.0"
+
+[addresses]
+scenario = "0xCAFE"
+"#;
+
+const MODULE: &str = r#"
+module scenario::counter {
+    struct Counter has key {
+        value: u64,
+    }
+
+    public fun publish(account: &signer, value: u64) {
+        move_to(account, Counter { value });
+    }
+
+    public fun bump(addr: address) acquires Counter {
+        let counter = borrow_global_mut<Counter>(addr);
+        assert!(counter.value < 10, 1);
+        counter.value = counter.value + 1;
+    }
+}
+"#;
+
+const PUBLISH_SCRIPT: &str = r#"
+script {
+    use scenario::counter;
+
+    fun publish_counter(account: signer, value: u64) {
+        counter::publish(&account, value);
+    }
+}
+"#;
+
+const BUMP_SCRIPT: &str = r#"
+script {
+    use scenario::counter;
+
+    fun bump(addr: address) {
+        counter::bump(addr);
+    }
+}
+"#;
+
+const PASSING_SCENARIO: &str = r#"
+[[steps]]
+action = "publish"
+path = "build/scenario/bytecode_modules/counter.mv"
+
+[[steps]]
+action = "execute"
+name = "publish the counter"
+path = "build/scenario/bytecode_scripts/publish_counter.mv"
+args = ["signer:0xCAFE", "u64:9"]
+
+[[steps]]
+action = "execute"
+path = "build/scenario/bytecode_scripts/bump.mv"
+args = ["address:0xCAFE"]
+
+[[steps]]
+action = "execute"
+name = "bump over the limit"
+path = "build/scenario/bytecode_scripts/bump.mv"
+args = ["address:0xCAFE"]
+expect_status = "ABORTED"
+expect_abort_code = 1
+
+[[steps]]
+action = "assert-resource"
+address = "0xCAFE"
+tag = "0xCAFE::counter::Counter"
+fields = { value = "10" }
+
+[[steps]]
+action = "assert-resource"
+address = "0xBEEF"
+tag = "0xCAFE::counter::Counter"
+exists = false
+"#;
+
+const FAILING_SCENARIO: &str = r#"
+[[steps]]
+action = "publish"
+path = "build/scenario/bytecode_modules/counter.mv"
+
+[[steps]]
+action = "execute"
+path = "build/scenario/bytecode_scripts/publish_counter.mv"
+args = ["signer:0xCAFE", "u64:1"]
+
+[[steps]]
+action = "assert-resource"
+name = "wrong value"
+address = "0xCAFE"
+tag = "0xCAFE::counter::Counter"
+fields = { value = "2" }
+
+[[steps]]
+action = "execute"
+name = "expected abort"
+path = "build/scenario/bytecode_scripts/bump.mv"
+args = ["address:0xCAFE"]
+expect_status = "ABORTED"
+"#;
+
+#[test]
+fn scenarios() {
+    let package = create_package(
+        "scenario",
+        &[
+            ("Move.toml", MANIFEST),
+            ("sources/counter.move", MODULE),
+            ("sources/publish_counter.move", PUBLISH_SCRIPT),
+            ("sources/bump.move", BUMP_SCRIPT),
+            ("passing.toml", PASSING_SCENARIO),
+            ("failing.toml", FAILING_SCENARIO),
+        ],
+    );
+    run(&package, &["build"]);
+
+    let passing = run(&package, &["scenario", "run", "passing.toml"]);
+    assert!(passing.contains("[PASS] 2. publish the counter"));
+    assert!(passing.contains("[PASS] 4. bump over the limit"));
+    assert!(passing.contains("6 of 6 steps passed"));
+
+    let failing = run_failing(&package, &["scenario", "run", "failing.toml"]);
+    assert!(
+        failing.contains("[PASS] 2. execute build/scenario/bytecode_scripts/publish_counter.mv")
+    );
+    assert!(failing.contains("[FAIL] 3. wrong value: the field 'value' is 1, expected 2"));
+    assert!(failing.contains("[FAIL] 4. expected abort: expected the status ABORTED"));
+    assert!(failing.contains("2 of 4 steps passed"));
+    assert!(failing.contains("2 of 4 steps failed"));
+
+    fs::remove_dir_all(&package).unwrap();
+}
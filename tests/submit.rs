@@ -0,0 +1,351 @@
+//! Signs and submits the pallet-move extrinsics to a node serving the submission RPC methods.
+
+mod common;
+
+use common::{create_package, run, run_failing};
+use jsonrpsee::server::{RpcModule, Server, SubscriptionMessage};
+use jsonrpsee::types::ErrorObjectOwned;
+use serde_json::json;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+/// Runtime metadata with the `System` events and the `MoveModule` calls and events.
+const METADATA: &[u8] = include_bytes!("fixtures/metadata.scale");
+
+/// Index of pallet-move in the fixture's runtime.
+const PALLET_INDEX: u8 = 42;
+
+/// Hash of every block of the node.
+const BLOCK_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+/// Genesis hash of the node.
+const GENESIS_HASH: &str = "0x2222222222222222222222222222222222222222222222222222222222222222";
+
+/// Alice's public key, the signer of the `--dev-account alice` extrinsics.
+const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+/// Length of the signed extrinsic's prefix: version, address, signature and nonce.
+const SIGNED_PREFIX_LEN: usize = 1 + 33 + 65 + 1;
+
+/// Extrinsics submitted to the node.
+type Extrinsics = Arc<Mutex<Vec<Vec<u8>>>>;
+
+/// Node including each submitted extrinsic in a block with the pallet's event.
+struct Node {
+    /// Node's URL.
+    url: String,
+    /// Extrinsics submitted to the node.
+    extrinsics: Extrinsics,
+}
+
+impl Node {
+    /// Starts the node on a free port.
+    fn start() -> Self {
+        let extrinsics = Extrinsics::default();
+        let module = rpc_module(extrinsics.clone());
+        let (address_tx, address_rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+                address_tx.send(server.local_addr().unwrap()).unwrap();
+                server.start(module).stopped().await;
+            });
+        });
+
+        Self {
+            url: format!("http://{}", address_rx.recv().unwrap()),
+            extrinsics,
+        }
+    }
+
+    /// Returns the call data of the last submitted extrinsic.
+    fn last_call(&self) -> Vec<u8> {
+        let extrinsics = self.extrinsics.lock().unwrap();
+        call(extrinsics.last().expect("no extrinsic submitted")).to_vec()
+    }
+}
+
+/// Registers the RPC methods used for the submission.
+fn rpc_module(extrinsics: Extrinsics) -> RpcModule<Extrinsics> {
+    let header = json!({
+        "parentHash": GENESIS_HASH,
+        "number": "0x1",
+        "stateRoot": GENESIS_HASH,
+        "extrinsicsRoot": GENESIS_HASH,
+        "digest": { "logs": [] },
+    });
+    let estimation = json!({
+        "gas_used": 100,
+        "vm_status_code": 4001,
+        "total_weight_including_gas_used": { "ref_time": 1000, "proof_size": 0 },
+    });
+
+    let mut module = RpcModule::new(extrinsics);
+    module
+        .register_method("chain_getFinalizedHead", |_, _| {
+            Ok::<_, ErrorObjectOwned>(BLOCK_HASH)
+        })
+        .unwrap();
+    module
+        .register_method("chain_getBlockHash", |_, _| {
+            Ok::<_, ErrorObjectOwned>(GENESIS_HASH)
+        })
+        .unwrap();
+    module
+        .register_method("state_getRuntimeVersion", |_, _| {
+            Ok::<_, ErrorObjectOwned>(json!({ "specVersion": 1, "transactionVersion": 1 }))
+        })
+        .unwrap();
+    module
+        .register_method("state_call", |params, _| {
+            let method: String = params.sequence().next()?;
+            let result = match method.as_str() {
+                // The metadata is returned as `Option<OpaqueMetadata>`.
+                "Metadata_metadata_at_version" => {
+                    [&[1][..], &compact(METADATA.len()), METADATA].concat()
+                }
+                "AccountNonceApi_account_nonce" => 0u32.to_le_bytes().to_vec(),
+                _ => return Err(ErrorObjectOwned::owned(-32601, method, None::<()>)),
+            };
+            Ok(hex_string(&result))
+        })
+        .unwrap();
+    module
+        .register_method("chain_getHeader", move |_, _| {
+            Ok::<_, ErrorObjectOwned>(header.clone())
+        })
+        .unwrap();
+    module
+        .register_method("chain_getBlock", |_, extrinsics| {
+            // Every extrinsic is included in its own block.
+            let extrinsics = extrinsics.lock().unwrap();
+            let last = extrinsics.last().map(|extrinsic| hex_string(extrinsic));
+            Ok::<_, ErrorObjectOwned>(json!({
+                "block": {
+                    "header": {
+                        "parentHash": GENESIS_HASH,
+                        "number": "0x1",
+                        "stateRoot": GENESIS_HASH,
+                        "extrinsicsRoot": GENESIS_HASH,
+                        "digest": { "logs": [] },
+                    },
+                    "extrinsics": Vec::from_iter(last),
+                },
+                "justifications": null,
+            }))
+        })
+        .unwrap();
+    module
+        .register_method("state_getStorage", |_, extrinsics| {
+            // The only storage value read is `System::Events`.
+            let extrinsics = extrinsics.lock().unwrap();
+            Ok::<_, ErrorObjectOwned>(
+                extrinsics
+                    .last()
+                    .map(|extrinsic| hex_string(&events(extrinsic))),
+            )
+        })
+        .unwrap();
+    for method in [
+        "mvm_estimateGasPublishModule",
+        "mvm_estimateGasPublishBundle",
+        "mvm_estimateGasExecuteScript",
+    ] {
+        let estimation = estimation.clone();
+        module
+            .register_method(method, move |_, _| {
+                Ok::<_, ErrorObjectOwned>(estimation.clone())
+            })
+            .unwrap();
+    }
+    module
+        .register_subscription(
+            "author_submitAndWatchExtrinsic",
+            "author_extrinsicUpdate",
+            "author_unwatchExtrinsic",
+            |params, pending, extrinsics| async move {
+                let extrinsic: String = params.one()?;
+                let extrinsic = hex::decode(extrinsic.trim_start_matches("0x"))?;
+                extrinsics.lock().unwrap().push(extrinsic);
+
+                let sink = pending.accept().await?;
+                for status in [json!("ready"), json!({ "inBlock": BLOCK_HASH })] {
+                    sink.send(SubscriptionMessage::from_json(&status)?).await?;
+                }
+
+                Ok(())
+            },
+        )
+        .unwrap();
+
+    module
+}
+
+/// Encodes the length as a SCALE compact integer, lengths up to 2^14 are supported.
+fn compact(len: usize) -> Vec<u8> {
+    match len {
+        0..=0x3f => vec![(len as u8) << 2],
+        0x40..=0x3fff => ((len as u16) << 2 | 1).to_le_bytes().to_vec(),
+        _ => unimplemented!("length {len} isn't used by the tests"),
+    }
+}
+
+/// Returns the `0x` prefixed hex string.
+fn hex_string(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Returns the signer's public key of the signed extrinsic.
+fn signer(extrinsic: &[u8]) -> &[u8] {
+    let start = length_prefix_len(extrinsic) + 2;
+    &extrinsic[start..start + 32]
+}
+
+/// Returns the call data of the signed extrinsic.
+fn call(extrinsic: &[u8]) -> &[u8] {
+    &extrinsic[length_prefix_len(extrinsic) + SIGNED_PREFIX_LEN..]
+}
+
+/// Returns the length of the extrinsic's compact length prefix.
+fn length_prefix_len(extrinsic: &[u8]) -> usize {
+    match extrinsic[0] & 0b11 {
+        0 => 1,
+        1 => 2,
+        _ => 4,
+    }
+}
+
+/// Encodes the `System::Events` of the block with the extrinsic: `ExtrinsicSuccess` followed by
+/// the pallet's event for the call.
+fn events(extrinsic: &[u8]) -> Vec<u8> {
+    let call_index = call(extrinsic)[1];
+    let mut pallet_event = vec![PALLET_INDEX, call_index];
+    if call_index == 0 {
+        // `ExecuteCalled` has a list of signers.
+        pallet_event.extend(compact(1));
+    }
+    pallet_event.extend(signer(extrinsic));
+
+    // Phase `ApplyExtrinsic(0)` and no topics for both events.
+    let apply_extrinsic = [0, 0, 0, 0, 0];
+    let no_topics = [0];
+    [
+        &compact(2)[..],
+        &apply_extrinsic,
+        &[0, 0],
+        &no_topics,
+        &apply_extrinsic,
+        &pallet_event,
+        &no_topics,
+    ]
+    .concat()
+}
+
+/// Call data: pallet and call indices followed by the SCALE-encoded arguments.
+fn expected_call(
+    call_index: u8,
+    artifact: &[u8],
+    gas_limit: u64,
+    cash_limit: Option<u128>,
+) -> Vec<u8> {
+    let mut call = vec![PALLET_INDEX, call_index];
+    call.extend(compact(artifact.len()));
+    call.extend(artifact);
+    call.extend(gas_limit.to_le_bytes());
+    if let Some(cash_limit) = cash_limit {
+        call.extend(cash_limit.to_le_bytes());
+    }
+    call
+}
+
+#[test]
+fn submit_extrinsics() {
+    let node = Node::start();
+    let script_tx = "script transaction";
+    let module = "module";
+    let bundle = "bundle";
+    let package = create_package(
+        "submit",
+        &[
+            ("script.mvt", script_tx),
+            ("module.mv", module),
+            ("bundle.mvb", bundle),
+        ],
+    );
+    let submit = |args: &[&str]| {
+        let mut submit_args = vec!["node", "--url", &node.url, "submit"];
+        submit_args.extend(args);
+        run(&package, &submit_args)
+    };
+
+    let execute = submit(&[
+        "execute",
+        "-s",
+        "script.mvt",
+        "--gas-limit",
+        "500",
+        "--cash-limit",
+        "7",
+        "--dev-account",
+        "alice",
+    ]);
+    assert!(execute.contains(&format!("Extrinsic included in block: {BLOCK_HASH}")));
+    assert!(execute.contains("Event: MoveModule::ExecuteCalled"));
+    assert_eq!(
+        node.last_call(),
+        expected_call(0, script_tx.as_bytes(), 500, Some(7))
+    );
+    assert_eq!(
+        hex::encode(signer(node.extrinsics.lock().unwrap().last().unwrap())),
+        ALICE
+    );
+
+    // Without the gas limit, the estimated gas plus the margin is used.
+    let publish_module = submit(&["publish-module", "-m", "module.mv", "--dev-account", "bob"]);
+    assert!(publish_module.contains("Estimated gas: 100, using gas limit: 110"));
+    assert!(publish_module.contains("Event: MoveModule::ModulePublished"));
+    assert_eq!(
+        node.last_call(),
+        expected_call(1, module.as_bytes(), 110, None)
+    );
+
+    let publish_bundle = submit(&[
+        "publish-bundle",
+        "-b",
+        "bundle.mvb",
+        "--seed",
+        "//Charlie",
+        "--gas-margin",
+        "50",
+    ]);
+    assert!(publish_bundle.contains("Estimated gas: 100, using gas limit: 150"));
+    assert!(publish_bundle.contains("Event: MoveModule::BundlePublished"));
+    assert_eq!(
+        node.last_call(),
+        expected_call(2, bundle.as_bytes(), 150, None)
+    );
+
+    let unknown_account = run_failing(
+        &package,
+        &[
+            "node",
+            "--url",
+            &node.url,
+            "submit",
+            "publish-module",
+            "-m",
+            "module.mv",
+            "--dev-account",
+            "mallory",
+        ],
+    );
+    assert!(unknown_account.contains("Unknown development account 'mallory'"));
+    assert_eq!(node.extrinsics.lock().unwrap().len(), 3);
+
+    fs::remove_dir_all(&package).unwrap();
+}